@@ -1,3 +1,5 @@
+use std::path::{Path, PathBuf};
+
 pub use lowerify::*;
 pub use meanify::*;
 pub use neat::*;
@@ -7,11 +9,7 @@ pub mod meanify;
 pub mod neat;
 
 pub fn parse_and_resolve(input: &str, filename: &str) -> Namespace {
-    let mut namespace = Namespace::new();
-    namespace.files.push(filename.to_string());
-
-    program(input, filename, &mut namespace);
-    namespace
+    parse_and_resolve_files(&[(filename, input)])
 }
 
 pub fn process_string(input: &str, filename: &str) -> Namespace {
@@ -20,9 +18,66 @@ pub fn process_string(input: &str, filename: &str) -> Namespace {
     namespace
 }
 
+/// Parse and resolve several files as one program, `files` holds pairs of filename and sourcecode.
+/// Each file gets its own file number in the source map, in the given order. Imports are looked
+/// up in the directories of the files, then in the standard library.
+pub fn parse_and_resolve_files(files: &[(&str, &str)]) -> Namespace {
+    let mut namespace = Namespace::new();
+    resolve_files(&mut namespace, files);
+    namespace
+}
+
+/// Parse and resolve the files into a namespace prepared by the caller, so an embedding program
+/// can register its builtins with `Namespace::add_builtin` first.
+pub fn resolve_files(namespace: &mut Namespace, files: &[(&str, &str)]) {
+    let mut project_dirs = Vec::new();
+    for (filename, _) in files {
+        let dir = match Path::new(filename).parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if !project_dirs.contains(&dir) {
+            project_dirs.push(dir);
+        }
+    }
+    namespace.search_paths.splice(0..0, project_dirs);
+
+    let mut programs = Vec::new();
+    for (filename, input) in files {
+        let file_no = namespace.add_file(filename, input);
+        programs.push((file_no, parse_file(input, file_no, namespace)));
+    }
+
+    let mut packages = Vec::new();
+    for (file_no, program) in &programs {
+        packages.extend(resolve_imports(program, *file_no, namespace));
+    }
+
+    let programs = programs
+        .into_iter()
+        .map(|(_, program)| program)
+        .chain(packages)
+        .collect::<Vec<_>>();
+
+    resolve_programs(&programs, namespace);
+}
+
+pub fn process_files(files: &[(&str, &str)]) -> Namespace {
+    let mut namespace = parse_and_resolve_files(files);
+    meanify(&mut namespace);
+    namespace
+}
+
 #[cfg(test)]
 mod test {
-    use crate::{codegen, process_string};
+    use crate::builtin::{HostFunction, HostLowering};
+    use crate::{
+        codegen, meanify, parse_and_resolve, process_files, process_string, resolve_files,
+        CodegenResult, Namespace,
+    };
+    use dc_hir::{BinOpKind, Type};
+    use dc_lexer::{ErrorType, Level, Loc};
+    use dc_mir::instruction::{Constant, ExprKind, Operand, TerminatorKind};
 
     #[test]
     #[rustfmt::skip]
@@ -53,4 +108,1278 @@ default$main() {你好();}
     fn should_run_function_after_main() {
         
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_call_function_from_other_file() {
+        let mut ns = process_files(&[
+            ("main.cj", "default$main() -> int {return say_hello();}"),
+            ("hello.cj", "default$say_hello() -> int {println(\"hello, world\"); return 7;}"),
+        ]);
+        assert!(!ns.any_errors());
+        assert_eq!(2, ns.source_map.len());
+        assert_eq!("main", ns.cfgs[0].name);
+        assert_eq!("say_hello", ns.cfgs[1].name);
+        assert!(matches!(&ns.cfgs[0].blocks[0].instructions[..], [ExprKind::Call { value, .. }] if value == "say_hello"));
+        // the exit code is the value returned by the function of the other file
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 7 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_duplicate_definition_across_files() {
+        let ns = process_files(&[
+            ("main.cj", "default$main() {}"),
+            ("other.cj", "pkg other\ndefault$main() {}"),
+        ]);
+        assert_eq!(1, ns.diagnostics.len());
+        assert_eq!("function ‘main’ is already defined", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(1, 18, 22)), ns.diagnostics[0].pos);
+        assert_eq!(Loc(0, 8, 12), ns.diagnostics[0].notes[0].pos);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_call_builtin_of_imported_package() {
+        let ns = process_string("import fmt
+default$main() {fmt.println(\"hello\");}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!("fmt", ns.packages[0].name);
+        assert_eq!(Some("fmt".to_string()), ns.files[1].package);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_unknown_package() {
+        let ns = process_string("import missing
+default$main() {fmt.println(\"hello\");}
+", "hello.cj");
+        assert_eq!(2, ns.diagnostics.len());
+        assert_eq!("package ‘missing’ not found", ns.diagnostics[0].message);
+        assert_eq!("‘fmt’ is not an imported package", ns.diagnostics[1].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_qualify_functions_by_package() {
+        let filename = "../docs/examples/import/main.cj";
+        let input = std::fs::read_to_string(filename).unwrap();
+        let mut ns = process_string(&input, filename);
+        assert!(!ns.any_errors());
+        assert_eq!("main", ns.cfgs[0].name);
+        assert_eq!("greeting.say_hello", ns.cfgs[1].name);
+        // `pkg examples` of the program file does not qualify `main`
+        assert_eq!(None, ns.files[0].package);
+        assert_eq!(Some("greeting".to_string()), ns.files[1].package);
+        assert!(matches!(&ns.cfgs[0].blocks[0].instructions[..], [ExprKind::Call { value, .. }] if value == "greeting.say_hello"));
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 0 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_import_path_with_alias() {
+        let ns = process_string("import \"../docs/examples/import/greeting\" as hi;
+default$main() {hi.say_hello();}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!("greeting.say_hello", ns.cfgs[1].name);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_resolve_grouped_imports() {
+        let ns = process_string("import (
+  fmt as f,
+  strings,
+)
+default$main() {f.println(\"hello\");}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!("fmt", ns.packages[0].name);
+        assert_eq!("strings", ns.packages[1].name);
+        assert_eq!(Some(0), ns.imported_package(0, "f"));
+        assert_eq!(None, ns.imported_package(0, "fmt"));
+        assert_eq!(Some(1), ns.imported_package(0, "strings"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_import_cycle() {
+        let filename = "fixtures/import-cycle/main.cj";
+        let input = std::fs::read_to_string(filename).unwrap();
+        let ns = process_string(&input, filename);
+        assert_eq!(1, ns.diagnostics.len());
+        assert_eq!("import cycle between packages ‘first’ -> ‘second’ -> ‘first’", ns.diagnostics[0].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_check_call_arguments() {
+        let ns = parse_and_resolve("
+default$add(int a, int b) -> int {return a;}
+default$main() {
+    add(1, \"two\");
+    add(1);
+}
+", "hello.cj");
+        assert_eq!(2, ns.diagnostics.len());
+        assert_eq!(ErrorType::TypeError, ns.diagnostics[0].ty);
+        assert_eq!("argument ‘b’ of ‘add’ expects ‘int256’, found ‘string’", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(0, 74, 79)), ns.diagnostics[0].pos);
+        assert_eq!("function ‘add’ expects 2 arguments, 1 provided", ns.diagnostics[1].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_check_returns() {
+        let ns = parse_and_resolve("
+default$name(bool flag) -> string {return flag;}
+default$nothing() {return 1;}
+default$missing() -> int {return;}
+default$main() {println(name(true));}
+", "hello.cj");
+        assert_eq!(3, ns.diagnostics.len());
+        assert_eq!("function ‘name’ returns ‘string’, found ‘bool’", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(0, 43, 47)), ns.diagnostics[0].pos);
+        assert_eq!("function ‘nothing’ has no return type, but returns ‘int8’", ns.diagnostics[1].message);
+        assert_eq!("function ‘missing’ must return a value of type ‘int256’", ns.diagnostics[2].message);
+        assert!(ns.diagnostics.iter().all(|diag| diag.ty == ErrorType::TypeError));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_check_builtin_arguments() {
+        let ns = parse_and_resolve("
+struct Summary {
+    FanIn: int
+}
+default$main() {println(Summary { FanIn: 1 });}
+", "hello.cj");
+        assert_eq!(1, ns.diagnostics.len());
+        assert_eq!(ErrorType::TypeError, ns.diagnostics[0].ty);
+        assert_eq!("builtin function ‘println’ cannot be called with arguments of type ‘Summary’", ns.diagnostics[0].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_print_runtime_values() {
+        let mut ns = process_string("
+import fmt
+default$main() {
+    let count: int = 3;
+    print(\"count: \");
+    println(count * 2);
+    fmt.print(count > 2);
+    println(true);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+
+        let prints: Vec<(&Type, bool)> = ns.cfgs[0].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Print { value, newline, .. } => Some((value.ty(), *newline)),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![
+            (&Type::String, false),
+            (&Type::Int(256), true),
+            (&Type::Bool, false),
+            (&Type::Bool, true),
+        ], prints);
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 0 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_assert() {
+        let mut ns = process_string("
+default$main() {
+    let count: int = 2;
+    assert(count > 1);
+    assert(count > 2, \"count must be greater than 2\");
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+
+        let cfg = &ns.cfgs[0];
+        let names: Vec<&str> = cfg.blocks.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(vec!["entry", "assert_failed", "assert_passed", "assert_failed", "assert_passed"], names);
+        assert!(matches!(cfg.blocks[0].terminator, Some(TerminatorKind::Branch { true_block: 2, false_block: 1, .. })));
+        assert!(matches!(cfg.blocks[1].instructions[..], [ExprKind::AssertFailure { message: None, .. }]));
+        assert!(matches!(cfg.blocks[3].instructions[..], [ExprKind::AssertFailure { message: Some(_), .. }]));
+        assert_eq!(Some(TerminatorKind::Unreachable), cfg.blocks[3].terminator);
+
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 1 }], codegen(&mut ns, "jit"));
+    }
+
+    extern "C" fn host_add(a: i64, b: i64) -> i64 {
+        a + b
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_call_host_builtins() {
+        let mut ns = Namespace::new();
+        ns.add_builtin(HostFunction {
+            name: "host_add".to_string(),
+            args: vec![Type::Int(64), Type::Int(64)],
+            ret: vec![Type::Int(64)],
+            doc: "add two numbers of the host".to_string(),
+            lowering: HostLowering::Callback(host_add as *const () as usize),
+        });
+        ns.add_builtin(HostFunction {
+            name: "abs".to_string(),
+            args: vec![Type::Int(32)],
+            ret: vec![Type::Int(32)],
+            doc: "absolute value, from the C library".to_string(),
+            lowering: HostLowering::Symbol("abs".to_string()),
+        });
+        resolve_files(&mut ns, &[("hello.cj", "
+default$main() -> int {
+    return host_add(abs(2 - 42), 2);
+}
+")]);
+        assert!(!ns.any_errors());
+        meanify(&mut ns);
+
+        let calls: Vec<(&str, usize)> = ns.cfgs[0].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { value, args, .. } => Some((value.as_str(), args.len())),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![("abs", 1), ("dc.host.host_add.0", 2)], calls);
+
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 42 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_check_host_builtin_arguments() {
+        let mut ns = Namespace::new();
+        ns.add_builtin(HostFunction {
+            name: "host_add".to_string(),
+            args: vec![Type::Int(64), Type::Int(64)],
+            ret: vec![Type::Int(64)],
+            doc: "add two numbers of the host".to_string(),
+            lowering: HostLowering::Symbol("host_add".to_string()),
+        });
+        resolve_files(&mut ns, &[("hello.cj", "
+default$main() {
+    host_add(\"one\", 2);
+    host_add(1);
+}
+")]);
+        assert_eq!(2, ns.diagnostics.len());
+        assert_eq!("builtin function ‘host_add’ cannot be called with arguments of type ‘string’, ‘int8’", ns.diagnostics[0].message);
+        assert_eq!("builtin function ‘host_add’ expects 2 arguments, 1 provided", ns.diagnostics[1].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_store_local_variables() {
+        let mut ns = process_string("
+default$main() -> int {
+    let a: int = 7;
+    let b: int = a;
+    let flag: bool = true;
+    return b;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let names: Vec<&str> = ns.cfgs[0].vars.iter().map(|var| var.name.as_str()).collect();
+        assert_eq!(vec!["a", "b", "flag"], names);
+        assert_eq!(3, ns.cfgs[0].blocks[0].instructions.len());
+        match &ns.cfgs[0].blocks[0].instructions[1] {
+            ExprKind::Store { var_no, value: Operand::Load { var_no: from, .. }, .. } => {
+                assert_eq!(1, *var_no);
+                assert_eq!(0, *from);
+            }
+            instr => panic!("unexpected instruction {:?}", instr),
+        }
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 7 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_resolve_variables_in_scope() {
+        let ns = parse_and_resolve("
+default$main() {
+    let a: int = b;
+    let a: int = 2;
+    let c: string = 3;
+}
+", "hello.cj");
+        assert_eq!(3, ns.diagnostics.len());
+        assert_eq!("‘b’ is not found", ns.diagnostics[0].message);
+        assert_eq!("‘a’ is already declared", ns.diagnostics[1].message);
+        assert_eq!(Loc(0, 26, 27), ns.diagnostics[1].notes[0].pos);
+        assert_eq!("variable ‘c’ has type ‘string’, found ‘int8’", ns.diagnostics[2].message);
+        assert_eq!(ErrorType::TypeError, ns.diagnostics[2].ty);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_integer_arithmetic() {
+        let mut ns = process_string("
+default$main() -> int {
+    let a: int = 1 + 2 * 3;
+    let b: int = -4;
+    let c: int = (a - b) / 2 % 3 << 1;
+    let d: bool = a >= c && !(b == 4) || false;
+    if (d) {
+        return c;
+    }
+    return 0;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let ops: Vec<BinOpKind> = ns.cfgs[0].blocks.iter().flat_map(|block| &block.instructions).filter_map(|instr| match instr {
+            ExprKind::BinaryOp { op, .. } => Some(*op),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![
+            BinOpKind::Mul, BinOpKind::Add,
+            BinOpKind::Sub, BinOpKind::Div, BinOpKind::Rem, BinOpKind::Shl,
+            BinOpKind::Ge, BinOpKind::Eq,
+        ], ops);
+        // arithmetic on literals is done in the type of the variable
+        match &ns.cfgs[0].blocks[0].instructions[0] {
+            ExprKind::BinaryOp { left: Operand::Constant { ty, .. }, .. } => assert_eq!(Type::Int(256), *ty),
+            instr => panic!("unexpected instruction {:?}", instr),
+        }
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 4 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_abort_on_invalid_division() {
+        // division by zero
+        let mut ns = process_string("
+default$divide(int a, int b) -> int {
+    return a / b;
+}
+default$main() -> int {
+    return divide(1, 0);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 2 }], codegen(&mut ns, "jit"));
+
+        // the smallest value divided by -1 overflows
+        let mut ns = process_string("
+default$divide(int8 a, int8 b) -> int8 {
+    return a / b;
+}
+default$main() -> int8 {
+    return divide(-128, -1);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 2 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_build_blocks_with_terminators() {
+        let mut ns = process_string("
+default$main() {
+    let a: bool = true || false;
+    return;
+    println(\"never\");
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let blocks = &ns.cfgs[0].blocks;
+        let names: Vec<&str> = blocks.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(vec!["entry", "or_rhs", "or_end", "unreachable"], names);
+        assert!(matches!(blocks[0].terminator, Some(TerminatorKind::Branch { true_block: 2, false_block: 1, .. })));
+        assert_eq!(Some(TerminatorKind::Goto { block: 2 }), blocks[1].terminator);
+        assert_eq!(Some(TerminatorKind::Return { value: None }), blocks[2].terminator);
+        assert_eq!(1, blocks[3].instructions.len());
+        assert_eq!(Some(TerminatorKind::Return { value: None }), blocks[3].terminator);
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 0 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_if_and_while() {
+        let mut ns = process_string("
+default$main() -> int {
+    let a: int = 1;
+    if (a > 0) {
+        println(\"positive\");
+    }
+    while (a < 10) {
+        a = a + 1;
+        if (a == 5) {
+            break;
+        } else {
+            continue;
+        }
+    }
+    return a;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let blocks = &ns.cfgs[0].blocks;
+        let names: Vec<&str> = blocks.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(vec!["entry", "then", "else", "endif", "cond", "while", "endwhile", "then", "else", "endif"], names);
+        assert!(matches!(blocks[4].terminator, Some(TerminatorKind::Branch { true_block: 5, false_block: 6, .. })));
+        // `break` leaves the loop, `continue` goes back to the condition
+        assert_eq!(Some(TerminatorKind::Goto { block: 6 }), blocks[7].terminator);
+        assert_eq!(Some(TerminatorKind::Goto { block: 4 }), blocks[8].terminator);
+        assert_eq!(Some(TerminatorKind::Goto { block: 4 }), blocks[9].terminator);
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 5 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_misplaced_loop_control() {
+        let ns = parse_and_resolve("
+default$main() {
+    break;
+    if (1) {
+        continue;
+    }
+    while (true) {
+        break;
+    }
+}
+", "hello.cj");
+        assert_eq!(3, ns.diagnostics.len());
+        assert_eq!("‘break’ outside of a loop", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(0, 22, 28)), ns.diagnostics[0].pos);
+        assert_eq!("‘continue’ outside of a loop", ns.diagnostics[1].message);
+        assert_eq!("condition of ‘if’ must be ‘bool’, found ‘int8’", ns.diagnostics[2].message);
+        assert_eq!(ErrorType::TypeError, ns.diagnostics[2].ty);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_pass_arguments_and_return_values() {
+        let mut ns = process_string("
+default$add(int a, int b) -> int {
+    return a + b;
+}
+default$main() -> int {
+    let sum: int = add(1, 2);
+    return sum;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(Some(TerminatorKind::Return { value: Some(Operand::Load { location: Loc(0, 47, 52), ty: Type::Int(256), var_no: 2 }) }), ns.cfgs[0].blocks[0].terminator);
+        match &ns.cfgs[1].blocks[0].instructions[0] {
+            ExprKind::Call { value, args, res, .. } => {
+                assert_eq!("add", value);
+                assert_eq!(2, args.len());
+                assert!(args.iter().all(|arg| *arg.ty() == Type::Int(256)));
+                assert_eq!(Some(1), *res);
+            }
+            instr => panic!("unexpected instruction {:?}", instr),
+        }
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 3 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_errors_of_every_argument() {
+        let ns = parse_and_resolve("
+default$add(int a, int b) -> int {
+    return a + b;
+}
+default$main() {
+    let sum: int = add(x, y);
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec!["‘x’ is not found", "‘y’ is not found"], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_missing_return() {
+        let ns = process_string("
+default$max(int a, int b) -> int {
+    if (a > b) {
+        return a;
+    }
+}
+default$sign(int a) -> int {
+    if (a < 0) {
+        return -1;
+    } else {
+        return 1;
+    }
+}
+default$main() {}
+", "hello.cj");
+        assert_eq!(1, ns.diagnostics.len());
+        assert_eq!("function ‘max’ does not return a value on every path", ns.diagnostics[0].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_check_operand_types() {
+        let ns = parse_and_resolve("
+default$main() {
+    let a: int = 1 + true;
+    let b: bool = \"x\" < 2;
+    let c: bool = !1;
+    let d: bool = true && 1;
+}
+", "hello.cj");
+        assert_eq!(4, ns.diagnostics.len());
+        assert_eq!("operator ‘+’ cannot be applied to ‘int8’ and ‘bool’", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(0, 35, 43)), ns.diagnostics[0].pos);
+        assert_eq!("operator ‘<’ cannot be applied to ‘string’ and ‘int8’", ns.diagnostics[1].message);
+        assert_eq!("operator ‘!’ cannot be applied to ‘int8’", ns.diagnostics[2].message);
+        assert_eq!("operator ‘&&’ cannot be applied to ‘bool’ and ‘int8’", ns.diagnostics[3].message);
+        assert!(ns.diagnostics.iter().all(|diag| diag.ty == ErrorType::TypeError));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_resolve_sized_integers() {
+        let mut ns = process_string("
+default$main() -> uint8 {
+    let small: int8 = 127;
+    let wide: int16 = 128;
+    let count: uint8 = 200;
+    let total: uint16 = count + 1;
+    let signed: int16 = count;
+    let narrow: uint8 = uint8(wide);
+    let max: uint256 = 115792089237316195423570985008687907853269984665640564039457584007913129639935;
+    if (count > 100) {
+        return count / 3;
+    }
+    return narrow;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let types: Vec<String> = ns.cfgs[0].vars.iter().take(7).map(|var| var.ty.to_string()).collect();
+        assert_eq!(vec!["int8", "int16", "uint8", "uint16", "int16", "uint8", "uint256"], types);
+
+        let casts: Vec<(String, String)> = ns.cfgs[0].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Cast { value, to, .. } => Some((value.ty().to_string(), to.to_string())),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![
+            ("uint8".to_string(), "uint16".to_string()),
+            ("uint8".to_string(), "int16".to_string()),
+            ("int16".to_string(), "uint8".to_string()),
+        ], casts);
+
+        // 200 divided as unsigned, a signed division of its bits would give 238
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 66 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_integer_errors() {
+        let ns = parse_and_resolve("
+default$main() {
+    let c: uint8 = 1;
+    let d: int8 = 1;
+    let e: bool = c == d;
+    let g: uint8 = uint8(300);
+    let h: bool = bool(c);
+    let i: uint8 = -c;
+    let j: uint256 = 115792089237316195423570985008687907853269984665640564039457584007913129639936;
+    let a: int8 = 128;
+    let b: uint8 = -1;
+    let f: int8 = c;
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "operator ‘==’ cannot be applied to ‘uint8’ and ‘int8’",
+            "number literal ‘300’ does not fit in ‘uint8’",
+            "cannot convert ‘uint8’ to ‘bool’",
+            "operator ‘-’ cannot be applied to ‘uint8’",
+            "number literal ‘115792089237316195423570985008687907853269984665640564039457584007913129639936’ does not fit in ‘uint256’",
+            "number literal ‘128’ does not fit in ‘int8’",
+            "number literal ‘-1’ does not fit in ‘uint8’",
+            "variable ‘f’ has type ‘int8’, found ‘uint8’",
+        ], messages);
+        assert!(ns.diagnostics.iter().all(|diag| diag.ty == ErrorType::TypeError));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_resolve_floats() {
+        let mut ns = process_string("
+default$main() -> int32 {
+    let half: float64 = 1.5;
+    let small: float32 = 2.0e-3;
+    let whole: float64 = 3;
+    let count: int16 = 4;
+    let total: float64 = half * float64(count) + small;
+    if (total >= 6.0 && small != 0.0) {
+        return int32(-total);
+    }
+    return int32(whole);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let types: Vec<String> = ns.cfgs[0].vars.iter().take(5).map(|var| var.ty.to_string()).collect();
+        assert_eq!(vec!["float64", "float32", "float64", "int16", "float64"], types);
+
+        let constants: Vec<String> = ns.cfgs[0].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Store { value: Operand::Constant { ty, value: Constant::Float { value }, .. }, .. } => Some(format!("{}: {}", value, ty)),
+            _ => None,
+        }).collect();
+        assert_eq!(vec!["1.5: float64", "0.002: float32", "3: float64"], constants);
+
+        let casts: Vec<(String, String)> = ns.cfgs[0].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Cast { value, to, .. } => Some((value.ty().to_string(), to.to_string())),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![
+            ("int16".to_string(), "float64".to_string()),
+            ("float32".to_string(), "float64".to_string()),
+        ], casts);
+
+        // 1.5 * 4 + 0.002 is truncated toward zero
+        assert_eq!(vec![CodegenResult::Jit { exit_code: -6 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_float_errors() {
+        let ns = parse_and_resolve("
+default$main() {
+    let a: float64 = 1.5;
+    let b: float32 = a;
+    let c: float64 = a << 1;
+    let d: int8 = 1;
+    let e: float32 = d + 0.5;
+    let f: int32 = 2.5;
+    let g: bool = bool(a);
+    let h: float32 = 16777217;
+    for (x in 0.5..2.5) {}
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "operator ‘<<’ cannot be applied to ‘float64’ and ‘int8’",
+            "operator ‘+’ cannot be applied to ‘int8’ and ‘float64’",
+            "cannot convert ‘float64’ to ‘bool’",
+            "range bounds must be integers, found ‘float64’ and ‘float64’",
+            "variable ‘b’ has type ‘float32’, found ‘float64’",
+            "variable ‘f’ has type ‘int32’, found ‘float64’",
+            "number literal ‘16777217’ does not fit in ‘float32’",
+        ], messages);
+        assert!(ns.diagnostics.iter().all(|diag| diag.ty == ErrorType::TypeError));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_for_loops() {
+        let mut ns = process_string("
+default$main() -> int {
+    let sum: int = 0;
+    for (let i: int = 0; i < 10; i++) {
+        if (i == 5) {
+            continue;
+        }
+        sum += i;
+    }
+    for (j in 0..3) {
+        sum -= j;
+    }
+    for (k in [1, 2]) {
+        sum *= k;
+    }
+    return sum;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let blocks = &ns.cfgs[0].blocks;
+        let names: Vec<&str> = blocks.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(vec![
+            "entry", "for_cond", "for_body", "for_next", "endfor", "then", "else", "endif",
+            "for_cond", "for_body", "for_next", "endfor",
+            "for_cond", "for_body", "for_next", "endfor", "then", "else", "endif",
+        ], names);
+        assert!(matches!(blocks[1].terminator, Some(TerminatorKind::Branch { true_block: 2, false_block: 4, .. })));
+        // `continue` runs the increment before checking the condition again
+        assert_eq!(Some(TerminatorKind::Goto { block: 3 }), blocks[5].terminator);
+        assert_eq!(Some(TerminatorKind::Goto { block: 1 }), blocks[3].terminator);
+        // the list is walked by an index, the value at the index is picked before the body
+        assert!(matches!(blocks[13].terminator, Some(TerminatorKind::Branch { true_block: 16, false_block: 17, .. })));
+        assert_eq!(Some(TerminatorKind::Goto { block: 14 }), blocks[18].terminator);
+        // (45 - 5 - 0 - 1 - 2) * 1 * 2
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 74 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_loop_and_labels() {
+        let mut ns = process_string("
+default$first(int limit) -> int {
+    let i: int = 0;
+    'outer: loop {
+        i++;
+        for (let j: int = 0; j < limit; j++) {
+            if (i * j > limit) {
+                break 'outer;
+            }
+            if (j == i) {
+                continue 'outer;
+            }
+        }
+    }
+    return i;
+}
+default$main() -> int {
+    return first(10);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let blocks = &ns.cfgs[0].blocks;
+        let names: Vec<&str> = blocks.iter().map(|block| block.name.as_str()).collect();
+        assert_eq!(vec![
+            "entry", "loop", "endloop", "for_cond", "for_body", "for_next", "endfor",
+            "then", "else", "endif", "then", "else", "endif",
+        ], names);
+        // the labeled loop is left from the inner one, or restarted
+        assert_eq!(Some(TerminatorKind::Goto { block: 2 }), blocks[7].terminator);
+        assert_eq!(Some(TerminatorKind::Goto { block: 1 }), blocks[10].terminator);
+        assert_eq!(Some(TerminatorKind::Goto { block: 1 }), blocks[6].terminator);
+        // 4 * 3 is the first product over the limit
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 4 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_unknown_labels() {
+        let ns = parse_and_resolve("
+default$main() {
+    'a: loop {
+        break 'b;
+        'a: while (true) {
+            continue 'a;
+        }
+    }
+}
+", "hello.cj");
+        assert_eq!(2, ns.diagnostics.len());
+        assert_eq!("use of undeclared label ‘'b’", ns.diagnostics[0].message);
+        assert_eq!(Some(Loc(0, 47, 49)), ns.diagnostics[0].pos);
+        assert_eq!("label ‘'a’ is already used by an enclosing loop", ns.diagnostics[1].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_compound_assignment() {
+        let mut ns = process_string("
+default$main() -> int {
+    let a: int = 1;
+    a = 2;
+    a <<= 3;
+    a--;
+    return a;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        let instructions = &ns.cfgs[0].blocks[0].instructions;
+        let ops: Vec<BinOpKind> = instructions.iter().filter_map(|instr| match instr {
+            ExprKind::BinaryOp { op, .. } => Some(*op),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![BinOpKind::Shl, BinOpKind::Sub], ops);
+        // the decrement stores the updated value back into ‘a’
+        assert!(matches!(instructions.last(), Some(ExprKind::Store { var_no: 0, .. })));
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 15 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_invalid_iteration() {
+        let ns = parse_and_resolve("
+default$main() {
+    for (i in true) {}
+    for (i in [1, false]) {}
+    for (i in 0..\"ten\") {}
+    let s: string = \"a\";
+    s += 1;
+    s++;
+}
+", "hello.cj");
+        assert_eq!(5, ns.diagnostics.len());
+        assert_eq!("only ranges and lists can be iterated", ns.diagnostics[0].message);
+        assert_eq!("list elements must have the same type, found ‘int8’ and ‘bool’", ns.diagnostics[1].message);
+        assert_eq!("range bounds must be integers, found ‘int8’ and ‘string’", ns.diagnostics[2].message);
+        assert_eq!("operator ‘+’ cannot be applied to ‘string’ and ‘int8’", ns.diagnostics[3].message);
+        assert_eq!("operator ‘++’ cannot be applied to ‘string’", ns.diagnostics[4].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_structs() {
+        let mut ns = process_string("
+struct Summary {
+    Name: string
+    FanIn: int
+}
+struct Hello {
+    summary: Summary
+    count: int
+}
+default$fan_in(Hello hello) -> int {
+    return hello.summary.FanIn;
+}
+default$main() -> int {
+    let hello: Hello = Hello { count: 1, summary: Summary { Name: \"a\", FanIn: 2 } };
+    hello.summary.FanIn = 3;
+    hello.count += fan_in(hello);
+    return hello.count;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(2, ns.structs.len());
+        assert_eq!(Type::Struct { struct_no: 0, name: "Summary".to_string() }, ns.structs[1].fields[0].ty);
+        assert_eq!(Type::Struct { struct_no: 1, name: "Hello".to_string() }, ns.functions[0].params[0].ty);
+
+        let instructions = &ns.cfgs[1].blocks[0].instructions;
+        assert!(instructions.iter().any(|instr| matches!(instr, ExprKind::StoreField { var_no: 0, fields, .. } if *fields == vec![0, 1])));
+        assert!(instructions.iter().any(|instr| matches!(instr, ExprKind::StoreField { var_no: 0, fields, .. } if *fields == vec![1])));
+        let literals: Vec<usize> = instructions.iter().filter_map(|instr| match instr {
+            ExprKind::StructLiteral { values, .. } => Some(values.len()),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![2, 2], literals);
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 4 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_struct_errors() {
+        let ns = parse_and_resolve("
+struct Node {
+    next: Node
+    size: int
+}
+struct Summary {
+    Name: string
+    Name: int
+}
+default$main() {
+    let s: Summary = Summary { Name: 1, Size: 2 };
+    let t: Summary = Summary { };
+    let u: Summary = Summary { Name: 1 };
+    let n: int = 1;
+    n.size = 2;
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "field ‘Name’ is already declared",
+            "struct ‘Node’ contains itself",
+            "struct ‘Summary’ has no field ‘Size’",
+            "missing field ‘Name’ of struct ‘Summary’",
+            "‘int256’ has no field ‘size’",
+            "field ‘Name’ of ‘Summary’ has type ‘string’, found ‘int8’",
+        ], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_unresolved_type_once() {
+        let ns = parse_and_resolve("
+default$take(int a) {
+}
+default$give() -> int {
+    let m: Missing = 1;
+    take(m);
+    let n: int = m;
+    if (m) {
+        n = m;
+    }
+    return m;
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec!["type ‘Missing’ not found"], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_call_methods() {
+        let mut ns = process_string("
+struct Summary {
+    FanIn: int
+    FanOut: int
+}
+struct Hello {
+    count: int
+}
+Summary$analysis(int weight) -> int {
+    return self.FanIn * weight + self.FanOut;
+}
+Hello$analysis() -> int {
+    return self.count;
+}
+default$analysis() -> int {
+    return 0;
+}
+default$main() {
+    let summary: Summary = Summary { FanIn: 1, FanOut: 2 };
+    let hello: Hello = Hello { count: 3 };
+    let total: int = summary.analysis(2) + hello.analysis() + analysis();
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(vec![0], ns.structs[0].functions);
+        assert_eq!(vec![1], ns.structs[1].functions);
+        assert_eq!("self", ns.functions[0].params[0].name);
+        let names: Vec<&str> = ns.cfgs.iter().map(|cfg| cfg.name.as_str()).collect();
+        assert_eq!(vec!["Summary$analysis", "Hello$analysis", "analysis", "main"], names);
+
+        let calls: Vec<(&str, usize)> = ns.cfgs[3].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { value, args, .. } => Some((value.as_str(), args.len())),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![("Summary$analysis", 2), ("Hello$analysis", 1), ("analysis", 0)], calls);
+        let _results = codegen(&mut ns, "jit");
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_method_errors() {
+        let ns = parse_and_resolve("
+struct Summary {
+    FanIn: int
+}
+Summary$analysis() -> int {
+    return self.FanIn;
+}
+Summary$analysis() -> int {
+    return 1;
+}
+Missing$analysis() {}
+default$main() {
+    let summary: Summary = Summary { FanIn: 1 };
+    let n: int = 1;
+    summary.build();
+    summary.analysis(1);
+    n.analysis();
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "method ‘analysis’ of ‘Summary’ is already defined",
+            "struct ‘Missing’ not found",
+            "struct ‘Summary’ has no method ‘build’",
+            "‘int256’ has no method ‘analysis’",
+            "function ‘analysis’ expects 0 arguments, 1 provided",
+        ], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_assign_fields_of_receiver() {
+        let mut ns = process_string("
+struct Counter {
+    count: int
+}
+struct Pair {
+    left: Counter
+}
+Counter$add(int n) {
+    self.count = self.count + n;
+}
+Counter$twice(int n) {
+    self.add(n);
+    self.add(n);
+}
+default$main() -> int {
+    let counter: Counter = Counter { count: 1 };
+    counter.add(2);
+    counter.twice(3);
+    let pair: Pair = Pair { left: counter };
+    pair.left.add(4);
+    return pair.left.count;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert!(ns.cfgs[0].method);
+        assert!(!ns.cfgs[2].method);
+
+        // the receiver is passed by the address of the variable, or of its field
+        let receivers: Vec<(usize, Vec<usize>)> = ns.cfgs[2].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { args, .. } => match &args[0] {
+                Operand::Address { var_no, fields, .. } => Some((*var_no, fields.clone())),
+                _ => None,
+            },
+            _ => None,
+        }).collect();
+        assert_eq!(vec![(0, vec![]), (0, vec![]), (1, vec![0])], receivers);
+
+        // 1 + 2 + 3 + 3 + 4, the changes of the methods are kept
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 13 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_resolve_grouped_methods() {
+        let mut ns = process_string("
+struct Summary {
+    FanIn: int
+}
+Summary$* {
+    build(int a) {
+        self.FanIn = a;
+    }
+    analysis() -> int {
+        return self.FanIn;
+    }
+}
+default$main() -> int {
+    let summary: Summary = Summary { FanIn: 1 };
+    summary.build(2);
+    let total: int = summary.analysis();
+    return total;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(vec![0, 1], ns.structs[0].functions);
+        let names: Vec<&str> = ns.cfgs.iter().map(|cfg| cfg.name.as_str()).collect();
+        assert_eq!(vec!["Summary$build", "Summary$analysis", "main"], names);
+        // the field set by ‘build’ is returned by ‘analysis’
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 2 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_grouped_method_errors() {
+        let ns = parse_and_resolve("
+struct Summary {
+    FanIn: int
+}
+Summary$analysis() -> int {
+    return self.FanIn;
+}
+Summary$* {
+    analysis() -> int {
+        return 1;
+    }
+}
+Missing$* {
+    build() {}
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "method ‘analysis’ of ‘Summary’ is already defined",
+            "struct ‘Missing’ not found",
+        ], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_constructors() {
+        let mut ns = process_string("
+struct Summary {
+    FanIn: int
+    FanOut: int
+}
+Summary$new(int fan_in) {
+    self.FanIn = fan_in;
+    if (fan_in > 1) {
+        self.FanOut = 1;
+        return;
+    }
+    self.FanOut = 0;
+}
+Summary$total() -> int {
+    return self.FanIn + self.FanOut;
+}
+default$main() -> int {
+    let first: Summary = Summary.new(1);
+    let second: Summary = Summary(2);
+    let total: int = first.total() + second.total();
+    return total;
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert!(ns.functions[0].constructor);
+        assert_eq!(vec!["fan_in"], ns.functions[0].params.iter().map(|param| param.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(vec!["fan_in", "self"], ns.functions[0].vars.iter().map(|var| var.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(Type::Struct { struct_no: 0, name: "Summary".to_string() }, ns.functions[0].returns[0].ty);
+
+        let calls: Vec<(&str, usize)> = ns.cfgs[2].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { value, args, .. } => Some((value.as_str(), args.len())),
+            _ => None,
+        }).collect();
+        assert_eq!(vec![("Summary$new", 1), ("Summary$new", 1), ("Summary$total", 1), ("Summary$total", 1)], calls);
+        // (1 + 0) + (2 + 1)
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 4 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_constructor_errors() {
+        let ns = parse_and_resolve("
+struct Summary {
+    FanIn: int
+    FanOut: int
+}
+struct Hello {
+    count: int
+}
+Summary$new(int fan_in) -> int {
+    if (fan_in > 1) {
+        return;
+    }
+    self.FanIn = fan_in;
+    while (true) {
+        self.FanOut = 1;
+    }
+}
+Hello$new() {
+    return 1;
+}
+default$main() {
+    let summary: Summary = Summary.new(1, 2);
+    let hello: Hello = Hello.create();
+    let other: Hello = hello.new();
+    let missing: Missing = Missing(1);
+    let other_missing: Missing = Missing.new(1);
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "constructor ‘new’ of ‘Summary’ cannot declare a return type",
+            "constructor of ‘Summary’ leaves field(s) ‘FanIn’, ‘FanOut’ uninitialized",
+            "constructor of ‘Hello’ cannot return a value",
+            "constructor of ‘Hello’ leaves field(s) ‘count’ uninitialized",
+            "struct ‘Hello’ has no constructor ‘create’",
+            "struct ‘Hello’ has no method ‘new’",
+            "type ‘Missing’ not found",
+            "type ‘Missing’ not found",
+            "function ‘new’ expects 1 arguments, 2 provided",
+        ], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_lower_fun_and_objects() {
+        let mut ns = process_string("
+fun add(int a, int b) -> int {
+    return a + b;
+}
+object Math {
+    fun max(int a, int b) -> int {
+        if (a > b) {
+            return a;
+        }
+        return b;
+    }
+    fun add(int a) -> int {
+        return max(a, 0) + 1;
+    }
+}
+fun main() {
+    let total: int = add(1, 2) + Math.max(3, 4) + Math.add(5);
+}
+", "hello.cj");
+        assert!(!ns.any_errors());
+        assert_eq!(1, ns.objects.len());
+        assert_eq!(vec![1, 2], ns.objects[0].functions);
+        let names: Vec<&str> = ns.cfgs.iter().map(|cfg| cfg.name.as_str()).collect();
+        assert_eq!(vec!["add", "Math.max", "Math.add", "main"], names);
+
+        // within the object, `max` is its own function
+        let calls: Vec<&str> = ns.cfgs[2].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { value, .. } => Some(value.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(vec!["Math.max"], calls);
+
+        let calls: Vec<&str> = ns.cfgs[3].blocks[0].instructions.iter().filter_map(|instr| match instr {
+            ExprKind::Call { value, .. } => Some(value.as_str()),
+            _ => None,
+        }).collect();
+        assert_eq!(vec!["add", "Math.max", "Math.add"], calls);
+
+        assert_eq!(vec![CodegenResult::Jit { exit_code: 0 }], codegen(&mut ns, "jit"));
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_fun_and_object_errors() {
+        let ns = parse_and_resolve("
+struct Summary {
+    FanIn: int
+}
+fun hello() {}
+default$hello() {}
+object Math {
+    fun zero() -> int {
+        return 0;
+    }
+    fun zero() -> int {
+        return 1;
+    }
+}
+object Math {}
+object Summary {}
+fun main() {
+    Math.one();
+    zero();
+}
+", "hello.cj");
+        let messages: Vec<&str> = ns.diagnostics.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(vec![
+            "function ‘hello’ is already defined",
+            "function ‘zero’ of object ‘Math’ is already defined",
+            "‘Math’ is already defined",
+            "‘Summary’ is already defined",
+            "object ‘Math’ has no function ‘one’",
+            "unknown function ‘zero’",
+        ], messages);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_parser_error() {
+        let ns = process_string("default$main() {", "hello.cj");
+        assert!(ns.any_errors());
+        assert_eq!(ErrorType::ParserError, ns.diagnostics[0].ty);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_keep_resolving_after_parser_error() {
+        let ns = process_string("
+default$main() {
+    println(;
+    say_hello();
+}
+", "hello.cj");
+        assert_eq!(2, ns.diagnostics.len());
+        assert_eq!(ErrorType::ParserError, ns.diagnostics[0].ty);
+        assert_eq!("unknown function ‘say_hello’", ns.diagnostics[1].message);
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_unknown_function() {
+        let mut ns = process_string("
+default$main() {say_hello();}
+", "hello.cj");
+        assert_eq!(1, ns.diagnostics.len());
+        assert_eq!("unknown function ‘say_hello’", ns.diagnostics[0].message);
+        assert_eq!(Level::Error, ns.diagnostics[0].level);
+        assert!(codegen(&mut ns, "jit").is_empty());
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_report_builtin_arguments() {
+        let ns = process_string("
+default$main() {println(\"hello\", \"world\");}
+", "hello.cj");
+        assert_eq!("builtin function ‘println’ expects 1 arguments, 2 provided", ns.diagnostics[0].message);
+    }
 }
@@ -1,13 +1,27 @@
+use std::collections::HashMap;
+
 use crate::code_object::CodeObject;
 use crate::ControlFlowGraph;
-use dc_mir::instruction::ExprKind;
-use inkwell::types::BasicTypeEnum;
-use inkwell::values::FunctionValue;
+use dc_mir::instruction::{ExprKind, TerminatorKind};
+use inkwell::basic_block::BasicBlock;
+use inkwell::types::{BasicType, BasicTypeEnum};
+use inkwell::values::{BasicValueEnum, FunctionValue, PointerValue};
+use inkwell::AddressSpace;
 
 pub trait BaseTarget<'a> {
-    fn emit_function(&self, sb: &mut CodeObject, cfg: &ControlFlowGraph) {
-        let function = self.create_llvm_function(sb, &cfg);
-        self.emit_cfg(sb, function, cfg);
+    /// Declare all functions before emitting any body, so a call does not depend on
+    /// the order of definition, which may span several files.
+    fn emit_functions(&self, sb: &mut CodeObject, cfgs: &[ControlFlowGraph]) {
+        sb.declare_host_functions();
+
+        let functions = cfgs
+            .iter()
+            .map(|cfg| self.create_llvm_function(sb, cfg))
+            .collect::<Vec<FunctionValue>>();
+
+        for (cfg, function) in cfgs.iter().zip(functions) {
+            self.emit_cfg(sb, function, cfg);
+        }
     }
 
     fn create_llvm_function<'func>(
@@ -15,35 +29,230 @@ pub trait BaseTarget<'a> {
         co: &mut CodeObject<'func>,
         cfg: &ControlFlowGraph,
     ) -> FunctionValue<'func> {
-        let ret_type = co.context.i32_type();
-        let args_types = std::iter::repeat(ret_type)
-            .take(cfg.params.len())
-            .map(|f| f.into())
+        let args_types = cfg
+            .params
+            .iter()
+            .enumerate()
+            .filter_map(|(param_no, param)| match co.llvm_type(&param.ty) {
+                Some(ty) if param_no == 0 && cfg.method => {
+                    Some(ty.ptr_type(AddressSpace::Generic).as_basic_type_enum())
+                }
+                ty => ty,
+            })
             .collect::<Vec<BasicTypeEnum>>();
         let args_types = args_types.as_slice();
 
-        let fn_type = co.context.i32_type().fn_type(args_types, false);
+        let fn_type = match cfg.returns.first().and_then(|ret| co.llvm_type(&ret.ty)) {
+            Some(ret_type) => ret_type.fn_type(args_types, false),
+            // the entry point returns the exit code of the process
+            None if cfg.name == "main" => co.context.i32_type().fn_type(args_types, false),
+            None => co.context.void_type().fn_type(args_types, false),
+        };
 
         let func_decl = co.module.add_function(&cfg.name, fn_type, None);
         func_decl
     }
 
-    fn emit_cfg(&self, sb: &mut CodeObject, function: FunctionValue, cfg: &ControlFlowGraph) {
-        let bb = sb.context.append_basic_block(function, &cfg.name);
-        sb.builder.position_at_end(bb);
+    /// Emit one LLVM basic block for each block of the control flow graph, the stack slots
+    /// of all variables are allocated in the entry block.
+    fn emit_cfg<'func>(
+        &self,
+        sb: &mut CodeObject<'func>,
+        function: FunctionValue<'func>,
+        cfg: &ControlFlowGraph,
+    ) {
+        let blocks = cfg
+            .blocks
+            .iter()
+            .map(|block| sb.context.append_basic_block(function, &block.name))
+            .collect::<Vec<BasicBlock>>();
+
+        sb.builder.position_at_end(blocks[0]);
+
+        // the receiver of a method stays in the slot of the caller
+        let mut slots = HashMap::new();
+        for (var_no, var) in cfg.vars.iter().enumerate() {
+            if var_no == 0 && cfg.method {
+                if let Some(receiver) = function.get_first_param() {
+                    slots.insert(var_no, receiver.into_pointer_value());
+                }
+            } else if let Some(ty) = sb.llvm_type(&var.ty) {
+                slots.insert(var_no, sb.builder.build_alloca(ty, &var.name));
+            }
+        }
+
+        // parameters are the first variables
+        for (var_no, value) in function
+            .get_param_iter()
+            .enumerate()
+            .skip(cfg.method as usize)
+        {
+            sb.builder.build_store(slots[&var_no], value);
+        }
+
+        for (block, bb) in cfg.blocks.iter().zip(&blocks) {
+            sb.builder.position_at_end(*bb);
+
+            for instr in &block.instructions {
+                self.emit_instruction(sb, function, instr, &slots);
+            }
 
-        for instr in &cfg.blocks.instructions {
-            match instr {
-                ExprKind::Var { .. } => {}
-                ExprKind::Call { value } => {
-                    sb.emit_call(value);
+            match &block.terminator {
+                Some(TerminatorKind::Goto { block }) => {
+                    sb.builder.build_unconditional_branch(blocks[*block]);
                 }
-                ExprKind::Print { value } => {
-                    sb.emit_print(&"", value);
+                Some(TerminatorKind::Branch {
+                    cond,
+                    true_block,
+                    false_block,
+                }) => {
+                    if let Some(cond) = sb.emit_operand(cond, &slots) {
+                        sb.builder.build_conditional_branch(
+                            cond.into_int_value(),
+                            blocks[*true_block],
+                            blocks[*false_block],
+                        );
+                    }
+                }
+                Some(TerminatorKind::Return { value }) => {
+                    match value
+                        .as_ref()
+                        .and_then(|value| sb.emit_operand(value, &slots))
+                    {
+                        Some(value) => {
+                            sb.builder.build_return(Some(&value));
+                        }
+                        None => sb.emit_void(function),
+                    }
+                }
+                Some(TerminatorKind::Unreachable) | None => {
+                    sb.builder.build_unreachable();
                 }
             }
         }
+    }
 
-        sb.emit_void();
+    fn emit_instruction<'func>(
+        &self,
+        sb: &mut CodeObject<'func>,
+        function: FunctionValue<'func>,
+        instr: &ExprKind,
+        slots: &HashMap<usize, PointerValue<'func>>,
+    ) {
+        match instr {
+            ExprKind::Var { .. } => {}
+            ExprKind::Store { var_no, value, .. } => {
+                if let (Some(slot), Some(value)) =
+                    (slots.get(var_no), sb.emit_operand(value, slots))
+                {
+                    sb.builder.build_store(*slot, value);
+                }
+            }
+            ExprKind::BinaryOp {
+                res,
+                op,
+                left,
+                right,
+                ..
+            } => {
+                let ty = left.ty();
+                if let (Some(left), Some(right)) =
+                    (sb.emit_operand(left, slots), sb.emit_operand(right, slots))
+                {
+                    let value = if ty.is_float() {
+                        sb.emit_float_binary(*op, left.into_float_value(), right.into_float_value())
+                    } else {
+                        sb.emit_binary(
+                            function,
+                            *op,
+                            left.into_int_value(),
+                            right.into_int_value(),
+                            ty.is_signed_integer(),
+                        )
+                        .into()
+                    };
+                    sb.builder.build_store(slots[res], value);
+                }
+            }
+            ExprKind::UnaryOp { res, op, value, .. } => {
+                if let Some(value) = sb.emit_operand(value, slots) {
+                    let value: BasicValueEnum = match value {
+                        BasicValueEnum::FloatValue(value) => {
+                            sb.builder.build_float_neg(value, "").into()
+                        }
+                        value => sb.emit_unary(*op, value.into_int_value()).into(),
+                    };
+                    sb.builder.build_store(slots[res], value);
+                }
+            }
+            ExprKind::Cast { res, value, to, .. } => {
+                let from = value.ty();
+                if let Some(value) = sb
+                    .emit_operand(value, slots)
+                    .and_then(|value| sb.emit_cast(value, &from, to))
+                {
+                    sb.builder.build_store(slots[res], value);
+                }
+            }
+            ExprKind::StructLiteral {
+                res, ty, values, ..
+            } => {
+                let values = values
+                    .iter()
+                    .filter_map(|value| sb.emit_operand(value, slots))
+                    .collect::<Vec<BasicValueEnum>>();
+
+                if let Some(ty) = sb.llvm_type(ty) {
+                    let value = sb.emit_struct_literal(ty.into_struct_type(), &values);
+                    sb.builder.build_store(slots[res], value);
+                }
+            }
+            ExprKind::StructMember {
+                res, value, field, ..
+            } => {
+                if let Some(value) = sb.emit_operand(value, slots) {
+                    let value = sb
+                        .builder
+                        .build_extract_value(value.into_struct_value(), *field as u32, "")
+                        .unwrap();
+                    sb.builder.build_store(slots[res], value);
+                }
+            }
+            ExprKind::StoreField {
+                var_no,
+                fields,
+                value,
+                ..
+            } => {
+                if let (Some(slot), Some(value)) =
+                    (slots.get(var_no), sb.emit_operand(value, slots))
+                {
+                    sb.emit_store_field(*slot, fields, value);
+                }
+            }
+            ExprKind::Call {
+                value, args, res, ..
+            } => {
+                let args = args
+                    .iter()
+                    .filter_map(|arg| sb.emit_operand(arg, slots))
+                    .collect::<Vec<BasicValueEnum>>();
+
+                if let (Some(ret), Some(res)) = (sb.emit_call(value, &args), res) {
+                    sb.builder.build_store(slots[res], ret);
+                }
+            }
+            ExprKind::AssertFailure { location, message } => {
+                let message = message
+                    .as_ref()
+                    .and_then(|message| sb.emit_operand(message, slots));
+                sb.emit_assert_failure(location, message);
+            }
+            ExprKind::Print { value, newline, .. } => {
+                if let Some(text) = sb.emit_operand(value, slots) {
+                    sb.emit_print(value.ty(), text, *newline);
+                }
+            }
+        }
     }
 }
@@ -1,14 +1,30 @@
+use std::collections::HashMap;
 use std::path::Path;
 
+use dc_hir::{BinOpKind, Type, UnOpKind};
+use dc_lexer::Loc;
+use dc_mir::instruction::{Constant, Operand};
+
+use inkwell::attributes::{Attribute, AttributeLoc};
 use inkwell::builder::Builder;
 use inkwell::context::Context;
 use inkwell::module::{Linkage, Module};
 use inkwell::targets::{CodeModel, FileType, RelocMode, TargetTriple};
-use inkwell::values::PointerValue;
-use inkwell::{AddressSpace, OptimizationLevel};
+use inkwell::types::{BasicType, BasicTypeEnum, FunctionType, IntType, StringRadix, StructType};
+use inkwell::values::{
+    BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue, StructValue,
+};
+use inkwell::{AddressSpace, FloatPredicate, IntPredicate, OptimizationLevel};
 
+use crate::builtin::HostLowering;
 use crate::Namespace;
 
+/// The exit code of a program stopped by a failed `assert`
+pub const ASSERT_EXIT_CODE: i32 = 1;
+
+/// The exit code of a program stopped by a division by zero, or an overflowing signed division
+pub const DIVISION_EXIT_CODE: i32 = 2;
+
 #[allow(dead_code)]
 #[derive(Debug)]
 pub struct CodeObject<'a> {
@@ -36,35 +52,765 @@ impl<'a> CodeObject<'a> {
         }
     }
 
-    pub(crate) fn emit_call(&self, name: &str) {
-        let opt = self.module.get_function(name);
-        match opt {
-            None => {}
-            Some(fun) => {
-                self.builder.build_call(fun, &[], name);
+    /// Call a function of the module, returns its return value if it has one
+    pub(crate) fn emit_call(
+        &self,
+        name: &str,
+        args: &[BasicValueEnum<'a>],
+    ) -> Option<BasicValueEnum<'a>> {
+        let fun = self.module.get_function(name)?;
+
+        self.builder
+            .build_call(fun, args, "")
+            .try_as_basic_value()
+            .left()
+    }
+
+    /// The LLVM type of a stack slot, `None` for types without a value
+    pub(crate) fn llvm_type(&self, ty: &Type) -> Option<BasicTypeEnum<'a>> {
+        match ty {
+            Type::Bool => Some(self.context.bool_type().as_basic_type_enum()),
+            Type::Int(bits) | Type::Uint(bits) => Some(
+                self.context
+                    .custom_width_int_type(*bits as u32)
+                    .as_basic_type_enum(),
+            ),
+            Type::Float(32) => Some(self.context.f32_type().as_basic_type_enum()),
+            Type::Float(_) => Some(self.context.f64_type().as_basic_type_enum()),
+            Type::Bytes(n) => Some(
+                self.context
+                    .custom_width_int_type(*n as u32 * 8)
+                    .as_basic_type_enum(),
+            ),
+            Type::String => Some(
+                self.context
+                    .i8_type()
+                    .ptr_type(AddressSpace::Generic)
+                    .as_basic_type_enum(),
+            ),
+            Type::Struct { struct_no, .. } => {
+                let fields = self.ns.structs[*struct_no]
+                    .fields
+                    .iter()
+                    .map(|field| self.llvm_type(&field.ty))
+                    .collect::<Option<Vec<BasicTypeEnum>>>()?;
+
+                Some(
+                    self.context
+                        .struct_type(&fields, false)
+                        .as_basic_type_enum(),
+                )
+            }
+            _ => None,
+        }
+    }
+
+    /// A struct value with the fields in the order of declaration
+    pub(crate) fn emit_struct_literal(
+        &self,
+        ty: StructType<'a>,
+        values: &[BasicValueEnum<'a>],
+    ) -> StructValue<'a> {
+        let mut value = ty.get_undef();
+
+        for (field, field_value) in values.iter().enumerate() {
+            value = self
+                .builder
+                .build_insert_value(value, *field_value, field as u32, "")
+                .unwrap()
+                .into_struct_value();
+        }
+
+        value
+    }
+
+    /// Store a value into a field of the struct in a stack slot, through nested structs:
+    /// the struct is loaded, the field replaced from the innermost struct outwards,
+    /// and the result stored back.
+    pub(crate) fn emit_store_field(
+        &self,
+        slot: PointerValue<'a>,
+        fields: &[usize],
+        value: BasicValueEnum<'a>,
+    ) {
+        let outer = self.builder.build_load(slot, "").into_struct_value();
+        let updated = self.emit_insert_field(outer, fields, value);
+
+        self.builder.build_store(slot, updated);
+    }
+
+    fn emit_insert_field(
+        &self,
+        value: StructValue<'a>,
+        fields: &[usize],
+        field_value: BasicValueEnum<'a>,
+    ) -> StructValue<'a> {
+        let (field, nested) = match fields.split_first() {
+            Some((field, nested)) => (*field as u32, nested),
+            None => return value,
+        };
+
+        let field_value = if nested.is_empty() {
+            field_value
+        } else {
+            let inner = self
+                .builder
+                .build_extract_value(value, field, "")
+                .unwrap()
+                .into_struct_value();
+            self.emit_insert_field(inner, nested, field_value).into()
+        };
+
+        self.builder
+            .build_insert_value(value, field_value, field, "")
+            .unwrap()
+            .into_struct_value()
+    }
+
+    pub(crate) fn emit_operand(
+        &self,
+        operand: &Operand,
+        slots: &HashMap<usize, PointerValue<'a>>,
+    ) -> Option<BasicValueEnum<'a>> {
+        match operand {
+            Operand::Load { var_no, .. } => slots
+                .get(var_no)
+                .map(|slot| self.builder.build_load(*slot, "")),
+            Operand::Constant { ty, value, .. } => match value {
+                Constant::Integer { value } => {
+                    let ty = self.llvm_type(ty)?.into_int_type();
+                    ty.const_int_from_string(&value.to_string(), StringRadix::Decimal)
+                        .map(BasicValueEnum::from)
+                }
+                Constant::Boolean { value } => Some(
+                    self.context
+                        .bool_type()
+                        .const_int(*value as u64, false)
+                        .into(),
+                ),
+                Constant::String { value } => Some(self.emit_c_string(value).into()),
+                Constant::Float { value } => {
+                    let ty = self.llvm_type(ty)?.into_float_type();
+                    Some(ty.const_float(*value).into())
+                }
+            },
+            Operand::Address { var_no, fields, .. } => {
+                let mut address = *slots.get(var_no)?;
+                for field in fields {
+                    address = self
+                        .builder
+                        .build_struct_gep(address, *field as u32, "")
+                        .ok()?;
+                }
+                Some(address.into())
+            }
+        }
+    }
+
+    /// Division, remainder, right shift and ordering follow the sign of the operands,
+    /// a division by zero, or an overflowing signed division, aborts the program
+    pub(crate) fn emit_binary(
+        &self,
+        function: FunctionValue<'a>,
+        op: BinOpKind,
+        left: IntValue<'a>,
+        right: IntValue<'a>,
+        signed: bool,
+    ) -> IntValue<'a> {
+        let predicate = |signed_predicate, unsigned_predicate| {
+            if signed {
+                signed_predicate
+            } else {
+                unsigned_predicate
+            }
+        };
+
+        match op {
+            BinOpKind::Add => self.builder.build_int_add(left, right, ""),
+            BinOpKind::Sub => self.builder.build_int_sub(left, right, ""),
+            BinOpKind::Mul => self.builder.build_int_mul(left, right, ""),
+            BinOpKind::Div => {
+                self.emit_division_check(function, left, right, signed);
+                if signed {
+                    self.builder.build_int_signed_div(left, right, "")
+                } else {
+                    self.builder.build_int_unsigned_div(left, right, "")
+                }
+            }
+            BinOpKind::Rem => {
+                self.emit_division_check(function, left, right, signed);
+                if signed {
+                    self.builder.build_int_signed_rem(left, right, "")
+                } else {
+                    self.builder.build_int_unsigned_rem(left, right, "")
+                }
             }
+            BinOpKind::And | BinOpKind::BitAnd => self.builder.build_and(left, right, ""),
+            BinOpKind::Or | BinOpKind::BitOr => self.builder.build_or(left, right, ""),
+            BinOpKind::BitXor => self.builder.build_xor(left, right, ""),
+            BinOpKind::Shl => self.builder.build_left_shift(left, right, ""),
+            BinOpKind::Shr => self.builder.build_right_shift(left, right, signed, ""),
+            BinOpKind::Eq => self.emit_compare(IntPredicate::EQ, left, right),
+            BinOpKind::Ne => self.emit_compare(IntPredicate::NE, left, right),
+            BinOpKind::Lt => {
+                self.emit_compare(predicate(IntPredicate::SLT, IntPredicate::ULT), left, right)
+            }
+            BinOpKind::Le => {
+                self.emit_compare(predicate(IntPredicate::SLE, IntPredicate::ULE), left, right)
+            }
+            BinOpKind::Gt => {
+                self.emit_compare(predicate(IntPredicate::SGT, IntPredicate::UGT), left, right)
+            }
+            BinOpKind::Ge => {
+                self.emit_compare(predicate(IntPredicate::SGE, IntPredicate::UGE), left, right)
+            }
+        }
+    }
+
+    /// Floats compare ordered, except `!=` which holds for a NaN operand
+    pub(crate) fn emit_float_binary(
+        &self,
+        op: BinOpKind,
+        left: FloatValue<'a>,
+        right: FloatValue<'a>,
+    ) -> BasicValueEnum<'a> {
+        let compare = |predicate| -> BasicValueEnum<'a> {
+            self.builder
+                .build_float_compare(predicate, left, right, "")
+                .into()
+        };
+
+        match op {
+            BinOpKind::Add => self.builder.build_float_add(left, right, "").into(),
+            BinOpKind::Sub => self.builder.build_float_sub(left, right, "").into(),
+            BinOpKind::Mul => self.builder.build_float_mul(left, right, "").into(),
+            BinOpKind::Div => self.builder.build_float_div(left, right, "").into(),
+            BinOpKind::Rem => self.builder.build_float_rem(left, right, "").into(),
+            BinOpKind::Eq => compare(FloatPredicate::OEQ),
+            BinOpKind::Ne => compare(FloatPredicate::UNE),
+            BinOpKind::Lt => compare(FloatPredicate::OLT),
+            BinOpKind::Le => compare(FloatPredicate::OLE),
+            BinOpKind::Gt => compare(FloatPredicate::OGT),
+            BinOpKind::Ge => compare(FloatPredicate::OGE),
+            _ => unreachable!("bitwise operators are rejected on floats"),
+        }
+    }
+
+    fn emit_compare(
+        &self,
+        predicate: IntPredicate,
+        left: IntValue<'a>,
+        right: IntValue<'a>,
+    ) -> IntValue<'a> {
+        self.builder.build_int_compare(predicate, left, right, "")
+    }
+
+    pub(crate) fn emit_unary(&self, op: UnOpKind, value: IntValue<'a>) -> IntValue<'a> {
+        match op {
+            UnOpKind::Not => self.builder.build_not(value, ""),
+            UnOpKind::Neg => self.builder.build_int_neg(value, ""),
+        }
+    }
+
+    /// Extend an integer by its sign, or truncate it, to the width of the other type
+    pub(crate) fn emit_int_cast(
+        &self,
+        value: IntValue<'a>,
+        to: IntType<'a>,
+        signed: bool,
+    ) -> IntValue<'a> {
+        let from = value.get_type().get_bit_width();
+
+        if from < to.get_bit_width() && signed {
+            self.builder.build_int_s_extend(value, to, "")
+        } else if from < to.get_bit_width() {
+            self.builder.build_int_z_extend(value, to, "")
+        } else if from > to.get_bit_width() {
+            self.builder.build_int_truncate(value, to, "")
+        } else {
+            value
+        }
+    }
+
+    /// Convert a number to another number type, a float is truncated toward zero
+    /// to an integer
+    pub(crate) fn emit_cast(
+        &self,
+        value: BasicValueEnum<'a>,
+        from: &Type,
+        to: &Type,
+    ) -> Option<BasicValueEnum<'a>> {
+        let to_type = self.llvm_type(to)?;
+
+        let value = match (from.is_float(), to.is_float()) {
+            (false, false) => self
+                .emit_int_cast(
+                    value.into_int_value(),
+                    to_type.into_int_type(),
+                    from.is_signed_integer(),
+                )
+                .into(),
+            (false, true) if from.is_signed_integer() => self
+                .builder
+                .build_signed_int_to_float(value.into_int_value(), to_type.into_float_type(), "")
+                .into(),
+            (false, true) => self
+                .builder
+                .build_unsigned_int_to_float(value.into_int_value(), to_type.into_float_type(), "")
+                .into(),
+            (true, false) if to.is_signed_integer() => self
+                .builder
+                .build_float_to_signed_int(value.into_float_value(), to_type.into_int_type(), "")
+                .into(),
+            (true, false) => self
+                .builder
+                .build_float_to_unsigned_int(value.into_float_value(), to_type.into_int_type(), "")
+                .into(),
+            (true, true) => {
+                let value = value.into_float_value();
+                let to_type = to_type.into_float_type();
+                match (from, to) {
+                    (Type::Float(from), Type::Float(to)) if from < to => {
+                        self.builder.build_float_ext(value, to_type, "").into()
+                    }
+                    (Type::Float(from), Type::Float(to)) if from > to => {
+                        self.builder.build_float_trunc(value, to_type, "").into()
+                    }
+                    _ => value.into(),
+                }
+            }
+        };
+
+        Some(value)
+    }
+
+    /// Abort with `DIVISION_EXIT_CODE` if the divisor is zero, or if a signed division
+    /// overflows, as the smallest value divided by -1 does. Continue in a new block otherwise.
+    fn emit_division_check(
+        &self,
+        function: FunctionValue<'a>,
+        dividend: IntValue<'a>,
+        divisor: IntValue<'a>,
+        signed: bool,
+    ) {
+        let ty = divisor.get_type();
+        let mut fails = self.emit_compare(IntPredicate::EQ, divisor, ty.const_zero());
+
+        if signed {
+            let shift = ty.const_int(ty.get_bit_width() as u64 - 1, false);
+            let min = ty.const_int(1, false).const_shl(shift);
+            let overflows = self.builder.build_and(
+                self.emit_compare(IntPredicate::EQ, dividend, min),
+                self.emit_compare(IntPredicate::EQ, divisor, ty.const_all_ones()),
+                "",
+            );
+            fails = self.builder.build_or(fails, overflows, "");
+        }
+
+        let trap = self.context.append_basic_block(function, "division_trap");
+        let next = self.context.append_basic_block(function, "division");
+        self.builder.build_conditional_branch(fails, trap, next);
+
+        self.builder.position_at_end(trap);
+        let i32_type = self.context.i32_type();
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let dprintf_type = i32_type.fn_type(&[i32_type.into(), str_type.into()], true);
+        let dprintf = self.external_function("dprintf", dprintf_type);
+        let stderr = i32_type.const_int(2, false).into();
+        let format = self.emit_c_string("division by zero or overflow\n").into();
+        self.builder.build_call(dprintf, &[stderr, format], "");
+
+        let code = i32_type.const_int(DIVISION_EXIT_CODE as u64, false);
+        self.builder
+            .build_call(self.abort_function(), &[code.into()], "");
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(next);
+    }
+
+    /// Print a value with `printf`, formatted by its type
+    pub(crate) fn emit_print(&self, ty: &Type, value: BasicValueEnum<'a>, newline: bool) {
+        let format = self.emit_c_string(if newline { "%s\n" } else { "%s" });
+
+        let text = match ty {
+            Type::Bool => self
+                .builder
+                .build_select(
+                    value.into_int_value(),
+                    self.emit_c_string("true"),
+                    self.emit_c_string("false"),
+                    "",
+                )
+                .into_pointer_value(),
+            Type::Int(_) | Type::Uint(_) => {
+                let print = self
+                    .print_int_function(value.into_int_value().get_type(), ty.is_signed_integer());
+                self.builder.build_call(print, &[value, format.into()], "");
+                return;
+            }
+            Type::Float(_) => {
+                // variadic arguments are passed as double
+                let mut value = value.into_float_value();
+                if *ty == Type::Float(32) {
+                    value = self
+                        .builder
+                        .build_float_ext(value, self.context.f64_type(), "");
+                }
+                let format = self.emit_c_string(if newline { "%g\n" } else { "%g" });
+                self.builder
+                    .build_call(self.printf_function(), &[format.into(), value.into()], "");
+                return;
+            }
+            _ => value.into_pointer_value(),
+        };
+
+        self.builder
+            .build_call(self.printf_function(), &[format.into(), text.into()], "");
+    }
+
+    fn printf_function(&self) -> FunctionValue<'a> {
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let printf_type = self.context.i32_type().fn_type(&[str_type.into()], true);
+
+        self.external_function("printf", printf_type)
+    }
+
+    /// A function of the C library, declared on first use
+    fn external_function(&self, name: &str, ty: FunctionType<'a>) -> FunctionValue<'a> {
+        match self.module.get_function(name) {
+            Some(function) => function,
+            None => self.module.add_function(name, ty, Some(Linkage::External)),
+        }
+    }
+
+    /// Declare the builtins registered by the embedding program, calls to them are
+    /// emitted like calls to functions of the module
+    pub(crate) fn declare_host_functions(&self) {
+        for (builtin_no, host) in self.ns.builtins.iter().enumerate() {
+            let args = host
+                .args
+                .iter()
+                .filter_map(|ty| self.llvm_type(ty))
+                .collect::<Vec<BasicTypeEnum>>();
+
+            let fn_type = match host.ret.first().and_then(|ty| self.llvm_type(ty)) {
+                Some(ret_type) => ret_type.fn_type(&args, false),
+                None => self.context.void_type().fn_type(&args, false),
+            };
+
+            self.external_function(&host.symbol_name(builtin_no), fn_type);
         }
     }
 
-    pub(crate) fn emit_print(&self, name: &&str, data: &str) {
-        let printf;
-        match self.module.get_function("puts") {
+    /// Print the position of the failed assertion and its message to stderr,
+    /// then abort the program with `ASSERT_EXIT_CODE`.
+    pub(crate) fn emit_assert_failure(&self, location: &Loc, message: Option<BasicValueEnum<'a>>) {
+        let i32_type = self.context.i32_type();
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let dprintf_type = i32_type.fn_type(&[i32_type.into(), str_type.into()], true);
+        let dprintf = self.external_function("dprintf", dprintf_type);
+
+        let stderr = i32_type.const_int(2, false).into();
+        let position = self
+            .emit_c_string(&self.ns.source_map.describe(location))
+            .into();
+        match message {
+            Some(message) => {
+                let format = self.emit_c_string("%s: assertion failed: %s\n").into();
+                self.builder
+                    .build_call(dprintf, &[stderr, format, position, message], "");
+            }
             None => {
-                let i32_type = self.context.i32_type();
-                let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
-                let printf_type = i32_type.fn_type(&[str_type.into()], true);
+                let format = self.emit_c_string("%s: assertion failed\n").into();
+                self.builder
+                    .build_call(dprintf, &[stderr, format, position], "");
+            }
+        }
+
+        let code = i32_type.const_int(ASSERT_EXIT_CODE as u64, false);
+        self.builder
+            .build_call(self.abort_function(), &[code.into()], "");
+    }
+
+    /// `dc.abort(code)` ends the program with the exit code. Run by the JIT, it jumps back
+    /// to the entry `dc.main`, which returns the code, else it calls `exit`.
+    fn abort_function(&self) -> FunctionValue<'a> {
+        if let Some(function) = self.module.get_function("dc.abort") {
+            return function;
+        }
+
+        let i32_type = self.context.i32_type();
+        let void_type = self.context.void_type();
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+
+        let function = self.module.add_function(
+            "dc.abort",
+            void_type.fn_type(&[i32_type.into()], false),
+            Some(Linkage::Internal),
+        );
+        let code = function.get_nth_param(0).unwrap();
 
-                printf = self
-                    .module
-                    .add_function("puts", printf_type, Some(Linkage::External));
+        let current = self.builder.get_insert_block();
+
+        let entry = self.context.append_basic_block(function, "entry");
+        let jump = self.context.append_basic_block(function, "jump");
+        let exit = self.context.append_basic_block(function, "exit");
+
+        self.builder.position_at_end(entry);
+        let (active, target) = self.abort_target();
+        let active = self.builder.build_load(active, "").into_int_value();
+        self.builder.build_conditional_branch(active, jump, exit);
+
+        self.builder.position_at_end(jump);
+        let longjmp = self.external_function(
+            "longjmp",
+            void_type.fn_type(&[str_type.into(), i32_type.into()], false),
+        );
+        self.builder.build_call(longjmp, &[target.into(), code], "");
+        self.builder.build_unreachable();
+
+        self.builder.position_at_end(exit);
+        let exit_function =
+            self.external_function("exit", void_type.fn_type(&[i32_type.into()], false));
+        self.builder.build_call(exit_function, &[code], "");
+        self.builder.build_unreachable();
+
+        if let Some(current) = current {
+            self.builder.position_at_end(current);
+        }
+
+        function
+    }
+
+    /// The flag set by `dc.main` once the `setjmp` buffer holds its context, and the buffer
+    /// as `i8*`. The buffer is larger than the `jmp_buf` of the supported platforms.
+    fn abort_target(&self) -> (PointerValue<'a>, PointerValue<'a>) {
+        let active = match self.module.get_global("dc.abort.active") {
+            Some(active) => active,
+            None => {
+                let ty = self.context.bool_type();
+                let active =
+                    self.module
+                        .add_global(ty, Some(AddressSpace::Generic), "dc.abort.active");
+                active.set_linkage(Linkage::Internal);
+                active.set_initializer(&ty.const_zero());
+                active
             }
-            Some(func) => {
-                printf = func;
+        };
+
+        let target = match self.module.get_global("dc.abort.target") {
+            Some(target) => target,
+            None => {
+                let ty = self.context.i64_type().array_type(64);
+                let target =
+                    self.module
+                        .add_global(ty, Some(AddressSpace::Generic), "dc.abort.target");
+                target.set_linkage(Linkage::Internal);
+                target.set_initializer(&ty.const_zero());
+                target.set_alignment(16);
+                target
             }
+        };
+
+        let target = self.builder.build_pointer_cast(
+            target.as_pointer_value(),
+            self.context.i8_type().ptr_type(AddressSpace::Generic),
+            "",
+        );
+
+        (active.as_pointer_value(), target)
+    }
+
+    /// `dc.main` runs `main` for the JIT: a failed assertion or division jumps back here
+    /// through `longjmp`, and its exit code is returned instead.
+    fn emit_jit_entry(&self) -> FunctionValue<'a> {
+        let i32_type = self.context.i32_type();
+        let str_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        let main = self.module.get_function("main").unwrap();
+
+        let function = self.module.add_function(
+            "dc.main",
+            i32_type.fn_type(&[], false),
+            Some(Linkage::External),
+        );
+
+        let entry = self.context.append_basic_block(function, "entry");
+        let aborted = self.context.append_basic_block(function, "aborted");
+        let run = self.context.append_basic_block(function, "run");
+
+        self.builder.position_at_end(entry);
+        let setjmp = self.external_function("setjmp", i32_type.fn_type(&[str_type.into()], false));
+        let returns_twice = self
+            .context
+            .create_enum_attribute(Attribute::get_named_enum_kind_id("returns_twice"), 0);
+        setjmp.add_attribute(AttributeLoc::Function, returns_twice);
+
+        let (active, target) = self.abort_target();
+        let code = self
+            .builder
+            .build_call(setjmp, &[target.into()], "")
+            .try_as_basic_value()
+            .left()
+            .unwrap()
+            .into_int_value();
+        self.builder
+            .build_store(active, self.context.bool_type().const_int(1, false));
+        let is_abort =
+            self.builder
+                .build_int_compare(IntPredicate::NE, code, i32_type.const_zero(), "");
+        self.builder
+            .build_conditional_branch(is_abort, aborted, run);
+
+        self.builder.position_at_end(aborted);
+        self.builder.build_return(Some(&code));
+
+        self.builder.position_at_end(run);
+        let value = self
+            .builder
+            .build_call(main, &[], "")
+            .try_as_basic_value()
+            .left();
+        let signed = self
+            .ns
+            .cfgs
+            .iter()
+            .find(|cfg| cfg.name == "main")
+            .and_then(|cfg| cfg.returns.first())
+            .map_or(true, |ret| ret.ty.is_signed_integer());
+        let exit_code = match value {
+            Some(BasicValueEnum::IntValue(value)) => self.emit_int_cast(value, i32_type, signed),
+            _ => i32_type.const_zero(),
+        };
+        self.builder.build_return(Some(&exit_code));
+
+        function
+    }
+
+    /// `printf` has no format for integers wider than 64 bits, so the decimal digits are
+    /// written into a buffer by a helper function, one for each integer type. The helper
+    /// takes the value and the format, which prints the digits as a string.
+    fn print_int_function(&self, ty: IntType<'a>, signed: bool) -> FunctionValue<'a> {
+        let bits = ty.get_bit_width();
+        let name = format!("dc.print.{}int{}", if signed { "" } else { "u" }, bits);
+        if let Some(function) = self.module.get_function(&name) {
+            return function;
         }
 
-        let pointer_value = self.emit_global_string(name, data.as_ref(), false);
-        self.builder.build_call(printf, &[pointer_value.into()], "");
+        let i8_type = self.context.i8_type();
+        let i32_type = self.context.i32_type();
+        let str_type = i8_type.ptr_type(AddressSpace::Generic);
+        let function_type = self
+            .context
+            .void_type()
+            .fn_type(&[ty.into(), str_type.into()], false);
+        let function = self
+            .module
+            .add_function(&name, function_type, Some(Linkage::Internal));
+
+        let current = self.builder.get_insert_block();
+
+        let entry = self.context.append_basic_block(function, "entry");
+        let digits = self.context.append_basic_block(function, "digits");
+        let sign = self.context.append_basic_block(function, "sign");
+        let minus = self.context.append_basic_block(function, "minus");
+        let print = self.context.append_basic_block(function, "print");
+
+        let value = function.get_nth_param(0).unwrap().into_int_value();
+        let format = function.get_nth_param(1).unwrap().into_pointer_value();
+
+        // the digits of the largest value, the sign and the terminating zero
+        let size = (bits as f64 * std::f64::consts::LOG10_2).ceil() as u64 + 2;
+        let zero = i32_type.const_zero();
+
+        self.builder.position_at_end(entry);
+        let buffer = self
+            .builder
+            .build_alloca(i8_type.array_type(size as u32), "buffer");
+        let end = i32_type.const_int(size - 1, false);
+        let end_ptr = unsafe { self.builder.build_gep(buffer, &[zero, end], "") };
+        self.builder.build_store(end_ptr, i8_type.const_zero());
+        let negative = if signed {
+            self.builder
+                .build_int_compare(IntPredicate::SLT, value, ty.const_zero(), "")
+        } else {
+            self.context.bool_type().const_zero()
+        };
+        // the magnitude as unsigned, which holds the smallest signed value as well
+        let magnitude = self
+            .builder
+            .build_select(negative, self.builder.build_int_neg(value, ""), value, "")
+            .into_int_value();
+        self.builder.build_unconditional_branch(digits);
+
+        self.builder.position_at_end(digits);
+        let rest = self.builder.build_phi(ty, "rest");
+        let position = self.builder.build_phi(i32_type, "position");
+        let next_position = self.builder.build_int_sub(
+            position.as_basic_value().into_int_value(),
+            i32_type.const_int(1, false),
+            "",
+        );
+        let ten = ty.const_int(10, false);
+        let digit =
+            self.builder
+                .build_int_unsigned_rem(rest.as_basic_value().into_int_value(), ten, "");
+        let digit = if bits > 8 {
+            self.builder.build_int_truncate(digit, i8_type, "")
+        } else {
+            digit
+        };
+        let digit = self
+            .builder
+            .build_int_add(digit, i8_type.const_int(b'0' as u64, false), "");
+        let digit_ptr = unsafe { self.builder.build_gep(buffer, &[zero, next_position], "") };
+        self.builder.build_store(digit_ptr, digit);
+        let next_rest =
+            self.builder
+                .build_int_unsigned_div(rest.as_basic_value().into_int_value(), ten, "");
+        let more = self
+            .builder
+            .build_int_compare(IntPredicate::NE, next_rest, ty.const_zero(), "");
+        self.builder.build_conditional_branch(more, digits, sign);
+        rest.add_incoming(&[(&magnitude, entry), (&next_rest, digits)]);
+        position.add_incoming(&[(&end, entry), (&next_position, digits)]);
+
+        self.builder.position_at_end(sign);
+        self.builder
+            .build_conditional_branch(negative, minus, print);
+
+        self.builder.position_at_end(minus);
+        let sign_position =
+            self.builder
+                .build_int_sub(next_position, i32_type.const_int(1, false), "");
+        let sign_ptr = unsafe { self.builder.build_gep(buffer, &[zero, sign_position], "") };
+        self.builder
+            .build_store(sign_ptr, i8_type.const_int(b'-' as u64, false));
+        self.builder.build_unconditional_branch(print);
+
+        self.builder.position_at_end(print);
+        let start = self.builder.build_phi(i32_type, "start");
+        start.add_incoming(&[(&next_position, sign), (&sign_position, minus)]);
+        let text = unsafe {
+            self.builder
+                .build_gep(buffer, &[zero, start.as_basic_value().into_int_value()], "")
+        };
+        self.builder
+            .build_call(self.printf_function(), &[format.into(), text.into()], "");
+        self.builder.build_return(None);
+
+        if let Some(current) = current {
+            self.builder.position_at_end(current);
+        }
+
+        function
+    }
+
+    /// A zero terminated string constant
+    fn emit_c_string(&self, value: &str) -> PointerValue<'a> {
+        let mut data = value.as_bytes().to_vec();
+        data.push(0);
+
+        self.emit_global_string("", &data, true)
     }
 
     fn emit_global_string(&self, name: &str, data: &[u8], constant: bool) -> PointerValue<'a> {
@@ -90,9 +836,14 @@ impl<'a> CodeObject<'a> {
         )
     }
 
-    pub fn emit_void(&mut self) {
-        self.builder
-            .build_return(Some(&self.context.i32_type().const_zero()));
+    /// Return without a value, the entry point returns the exit code zero
+    pub fn emit_void(&mut self, function: FunctionValue<'a>) {
+        match function.get_type().get_return_type() {
+            Some(_) => self
+                .builder
+                .build_return(Some(&self.context.i32_type().const_zero())),
+            None => self.builder.build_return(None),
+        };
     }
 
     pub fn bitcode(&self, path: &Path) {
@@ -101,15 +852,21 @@ impl<'a> CodeObject<'a> {
 
     pub fn run_jit(&self) -> i32 {
         self.module.get_function("main").unwrap().verify(true);
+        self.emit_jit_entry().verify(true);
 
         let ee = self
             .module
             .create_jit_execution_engine(OptimizationLevel::None)
             .unwrap();
-        let maybe_fn = unsafe {
-            // todo: thinking in return of main func
-            ee.get_function::<unsafe extern "C" fn() -> i32>("main")
-        };
+
+        for (builtin_no, host) in self.ns.builtins.iter().enumerate() {
+            if let HostLowering::Callback(address) = host.lowering {
+                if let Some(function) = self.module.get_function(&host.symbol_name(builtin_no)) {
+                    ee.add_global_mapping(&function, address);
+                }
+            }
+        }
+        let maybe_fn = unsafe { ee.get_function::<unsafe extern "C" fn() -> i32>("dc.main") };
 
         let compiled_fn = match maybe_fn {
             Ok(f) => f,
@@ -52,10 +52,14 @@ impl CharjTarget {
     }
 }
 
+/// Nothing is generated when the namespace has errors, check `Namespace::any_errors` first.
 pub fn codegen(ns: &mut Namespace, target: &str) -> Vec<CodegenResult> {
     let mut results = vec![];
+    if ns.any_errors() {
+        return results;
+    }
 
-    let filename = ns.files[0].clone();
+    let filename = ns.source_map.file(0).name.clone();
     let context = Context::create();
 
     match target {
@@ -15,9 +15,7 @@ impl WasmTarget {
 
         let wasm_target = "wasm32-unknown-unknown-wasm";
         let mut structure = CodeObject::new(context, filename, ns, wasm_target);
-        for cfg in &ns.cfgs {
-            target.emit_function(&mut structure, &cfg);
-        }
+        target.emit_functions(&mut structure, &ns.cfgs);
 
         structure
     }
@@ -1,14 +1,21 @@
-use dc_hir::Parameter;
+use dc_hir::{Parameter, Variable};
 use dc_mir::basic_block::BasicBlock;
-use dc_mir::instruction::ExprKind;
+use dc_mir::instruction::{ExprKind, TerminatorKind};
 
 ///  which is a [Control-flow graph](https://en.wikipedia.org/wiki/Control-flow_graph)
 #[derive(Clone, Debug)]
 pub struct ControlFlowGraph {
     pub name: String,
-    pub blocks: BasicBlock,
+    /// the first block is the entry of the function
+    pub blocks: Vec<BasicBlock>,
     pub params: Vec<Parameter>,
     pub returns: Vec<Parameter>,
+    /// a method, its first parameter is the address of the receiver `self`
+    pub method: bool,
+    /// the stack slots of the function, indexed by variable number
+    pub vars: Vec<Variable>,
+    /// the block instructions are emitted into
+    current: usize,
 }
 
 #[allow(dead_code)]
@@ -16,22 +23,73 @@ impl ControlFlowGraph {
     pub fn new(name: String) -> Self {
         ControlFlowGraph {
             name,
-            blocks: Default::default(),
+            blocks: vec![BasicBlock::new("entry")],
             params: vec![],
             returns: vec![],
+            method: false,
+            vars: vec![],
+            current: 0,
         }
     }
 
     pub fn placeholder() -> Self {
         ControlFlowGraph {
             name: "".to_string(),
-            blocks: Default::default(),
+            blocks: vec![],
             params: vec![],
             returns: vec![],
+            method: false,
+            vars: vec![],
+            current: 0,
         }
     }
 
+    /// Add an empty block, returns its number
+    pub fn new_basic_block(&mut self, name: &str) -> usize {
+        self.blocks.push(BasicBlock::new(name));
+        self.blocks.len() - 1
+    }
+
+    pub fn set_basic_block(&mut self, block: usize) {
+        self.current = block;
+    }
+
+    pub fn current_block(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.blocks[self.current].is_terminated()
+    }
+
+    /// Whether control can reach the block from the entry of the function
+    pub fn is_reachable(&self, block: usize) -> bool {
+        let mut visited = vec![false; self.blocks.len()];
+        let mut pending = vec![0];
+
+        while let Some(no) = pending.pop() {
+            if visited[no] {
+                continue;
+            }
+            visited[no] = true;
+
+            if let Some(terminator) = &self.blocks[no].terminator {
+                pending.extend(terminator.successors());
+            }
+        }
+
+        visited[block]
+    }
+
     pub fn emit(&mut self, instruction: ExprKind) {
-        self.blocks.instructions.push(instruction);
+        self.blocks[self.current].instructions.push(instruction);
+    }
+
+    /// End the current block
+    pub fn terminate(&mut self, terminator: TerminatorKind) {
+        let block = &mut self.blocks[self.current];
+        debug_assert!(!block.is_terminated(), "block {} ends twice", block.name);
+
+        block.terminator = Some(terminator);
     }
 }
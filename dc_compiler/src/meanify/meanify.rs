@@ -1,6 +1,10 @@
+use crate::meanify::variable_table::VariableTable;
+use crate::neat::expression::place;
 use crate::{ControlFlowGraph, Namespace};
-use dc_hir::{Builtin, Expression, Function, Statement};
-use dc_mir::instruction::ExprKind;
+use dc_hir::{BinOpKind, Builtin, Expression, Function, Statement, Type};
+use dc_lexer::{Diagnostic, Loc};
+use dc_mir::instruction::{Constant, ExprKind, Operand, TerminatorKind};
+use num_bigint::BigInt;
 
 pub fn meanify(ns: &mut Namespace) {
     #[allow(unused_assignments)]
@@ -18,102 +22,693 @@ pub fn meanify(ns: &mut Namespace) {
 }
 
 pub fn function_cfg(function_no: usize, ns: &mut Namespace) {
-    let func = &ns.functions[function_no];
+    let func = ns.functions[function_no].clone();
 
-    let func_name = &func.name;
-    let mut cfg = ControlFlowGraph::new(func_name.to_string());
+    let mut cfg = ControlFlowGraph::new(func.symbol_name());
     cfg.params = func.params.clone();
     cfg.returns = func.returns.clone();
+    cfg.method = func.is_method();
+
+    let mut vartab = VariableTable::new(&func.vars);
+    let mut loops = LoopScopes::new();
 
     for stmt in &func.body {
-        statement_cfg(stmt, func, &mut cfg, ns)
+        statement_cfg(stmt, &func, &mut cfg, &mut vartab, &mut loops, ns)
+    }
+
+    if !cfg.is_terminated() {
+        if func.returns.is_empty() {
+            cfg.terminate(TerminatorKind::Return { value: None });
+        } else {
+            if cfg.is_reachable(cfg.current_block()) {
+                ns.diagnostics.push(Diagnostic::error(
+                    func.location,
+                    format!(
+                        "function ‘{}’ does not return a value on every path",
+                        func.name
+                    ),
+                ));
+            }
+            cfg.terminate(TerminatorKind::Unreachable);
+        }
     }
 
+    cfg.vars = vartab.into_vars();
+
     ns.cfgs.push(cfg);
 }
 
+/// The blocks `break` and `continue` jump to, for each loop around the current statement
+#[derive(Default)]
+pub struct LoopScopes(Vec<LoopScope>);
+
+struct LoopScope {
+    label: Option<String>,
+    break_block: usize,
+    continue_block: usize,
+}
+
+impl LoopScopes {
+    pub fn new() -> Self {
+        LoopScopes(Vec::new())
+    }
+
+    pub fn enter(&mut self, label: &Option<String>, break_block: usize, continue_block: usize) {
+        self.0.push(LoopScope {
+            label: label.clone(),
+            break_block,
+            continue_block,
+        });
+    }
+
+    pub fn leave(&mut self) {
+        self.0.pop();
+    }
+
+    /// The innermost loop, or the innermost one with the label. The misplaced ones are
+    /// reported when resolving the function body.
+    fn find(&self, label: &Option<String>) -> Option<&LoopScope> {
+        self.0
+            .iter()
+            .rev()
+            .find(|scope| label.is_none() || scope.label == *label)
+    }
+
+    pub fn break_block(&self, label: &Option<String>) -> Option<usize> {
+        self.find(label).map(|scope| scope.break_block)
+    }
+
+    pub fn continue_block(&self, label: &Option<String>) -> Option<usize> {
+        self.find(label).map(|scope| scope.continue_block)
+    }
+}
+
 pub fn statement_cfg(
     stmt: &Statement,
     _func: &Function,
     cfg: &mut ControlFlowGraph,
-    ns: &Namespace,
+    vartab: &mut VariableTable,
+    loops: &mut LoopScopes,
+    ns: &mut Namespace,
 ) {
+    // code after a return has no predecessor, but still needs a block
+    if cfg.is_terminated() {
+        let block = cfg.new_basic_block("unreachable");
+        cfg.set_basic_block(block);
+    }
+
     match stmt {
-        Statement::VariableDecl { location: _ } => {
-            // todo
+        Statement::VariableDecl {
+            location,
+            var_no,
+            value,
+        }
+        | Statement::Assign {
+            location,
+            var_no,
+            value,
+        } => {
+            if let Some(value) = expression_cfg(value, cfg, vartab, ns) {
+                cfg.emit(ExprKind::Store {
+                    location: *location,
+                    var_no: *var_no,
+                    value,
+                });
+            }
+        }
+        Statement::AssignField {
+            location,
+            var_no,
+            fields,
+            value,
+        } => {
+            if let Some(value) = expression_cfg(value, cfg, vartab, ns) {
+                cfg.emit(ExprKind::StoreField {
+                    location: *location,
+                    var_no: *var_no,
+                    fields: fields.clone(),
+                    value,
+                });
+            }
         }
         Statement::Expression {
             location: _,
             expression: expr,
         } => {
-            expression_cfg(expr, cfg, ns);
+            expression_cfg(expr, cfg, vartab, ns);
+        }
+        Statement::Return { value, .. } => {
+            let value = value
+                .as_ref()
+                .and_then(|value| expression_cfg(value, cfg, vartab, ns));
+
+            cfg.terminate(TerminatorKind::Return { value });
+        }
+        Statement::If {
+            cond,
+            then,
+            otherwise,
+            ..
+        } => {
+            let cond = match expression_cfg(cond, cfg, vartab, ns) {
+                Some(cond) => cond,
+                None => return,
+            };
+
+            let then_block = cfg.new_basic_block("then");
+            let else_block = cfg.new_basic_block("else");
+            let end_block = cfg.new_basic_block("endif");
+
+            cfg.terminate(TerminatorKind::Branch {
+                cond,
+                true_block: then_block,
+                false_block: else_block,
+            });
+
+            for (block, body) in &[(then_block, then), (else_block, otherwise)] {
+                cfg.set_basic_block(*block);
+                for stmt in body.iter() {
+                    statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+                }
+                if !cfg.is_terminated() {
+                    cfg.terminate(TerminatorKind::Goto { block: end_block });
+                }
+            }
+
+            cfg.set_basic_block(end_block);
+        }
+        Statement::While {
+            label, cond, body, ..
+        } => {
+            let cond_block = cfg.new_basic_block("cond");
+            let body_block = cfg.new_basic_block("while");
+            let end_block = cfg.new_basic_block("endwhile");
+
+            cfg.terminate(TerminatorKind::Goto { block: cond_block });
+
+            cfg.set_basic_block(cond_block);
+            match expression_cfg(cond, cfg, vartab, ns) {
+                Some(cond) => cfg.terminate(TerminatorKind::Branch {
+                    cond,
+                    true_block: body_block,
+                    false_block: end_block,
+                }),
+                None => cfg.terminate(TerminatorKind::Unreachable),
+            }
+
+            cfg.set_basic_block(body_block);
+            loops.enter(label, end_block, cond_block);
+            for stmt in body {
+                statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+            }
+            loops.leave();
+            if !cfg.is_terminated() {
+                cfg.terminate(TerminatorKind::Goto { block: cond_block });
+            }
+
+            cfg.set_basic_block(end_block);
+        }
+        Statement::For {
+            label,
+            init,
+            cond,
+            next,
+            body,
+            ..
+        } => {
+            for stmt in init {
+                statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+            }
+
+            let cond_block = cfg.new_basic_block("for_cond");
+            let body_block = cfg.new_basic_block("for_body");
+            let next_block = cfg.new_basic_block("for_next");
+            let end_block = cfg.new_basic_block("endfor");
+
+            cfg.terminate(TerminatorKind::Goto { block: cond_block });
+
+            cfg.set_basic_block(cond_block);
+            match cond {
+                Some(cond) => match expression_cfg(cond, cfg, vartab, ns) {
+                    Some(cond) => cfg.terminate(TerminatorKind::Branch {
+                        cond,
+                        true_block: body_block,
+                        false_block: end_block,
+                    }),
+                    None => cfg.terminate(TerminatorKind::Unreachable),
+                },
+                None => cfg.terminate(TerminatorKind::Goto { block: body_block }),
+            }
+
+            cfg.set_basic_block(body_block);
+            loops.enter(label, end_block, next_block);
+            for stmt in body {
+                statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+            }
+            loops.leave();
+            if !cfg.is_terminated() {
+                cfg.terminate(TerminatorKind::Goto { block: next_block });
+            }
+
+            cfg.set_basic_block(next_block);
+            for stmt in next {
+                statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+            }
+            cfg.terminate(TerminatorKind::Goto { block: cond_block });
+
+            cfg.set_basic_block(end_block);
+        }
+        Statement::Loop { label, body, .. } => {
+            let body_block = cfg.new_basic_block("loop");
+            let end_block = cfg.new_basic_block("endloop");
+
+            cfg.terminate(TerminatorKind::Goto { block: body_block });
+
+            cfg.set_basic_block(body_block);
+            loops.enter(label, end_block, body_block);
+            for stmt in body {
+                statement_cfg(stmt, _func, cfg, vartab, loops, ns);
+            }
+            loops.leave();
+            if !cfg.is_terminated() {
+                cfg.terminate(TerminatorKind::Goto { block: body_block });
+            }
+
+            cfg.set_basic_block(end_block);
+        }
+        Statement::Break { label, .. } => {
+            if let Some(block) = loops.break_block(label) {
+                cfg.terminate(TerminatorKind::Goto { block });
+            }
+        }
+        Statement::Continue { label, .. } => {
+            if let Some(block) = loops.continue_block(label) {
+                cfg.terminate(TerminatorKind::Goto { block });
+            }
         }
     }
 }
 
-pub fn expression_cfg(expr: &Expression, cfg: &mut ControlFlowGraph, ns: &Namespace) -> Expression {
+/// Emit the instructions computing an expression, returns the operand holding its value,
+/// or `None` if it has no value.
+pub fn expression_cfg(
+    expr: &Expression,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut VariableTable,
+    ns: &mut Namespace,
+) -> Option<Operand> {
     match expr {
-        Expression::Placeholder => Expression::Placeholder,
-        Expression::StringLiteral {
-            location: _,
-            value: _,
-        } => expr.clone(),
+        Expression::Placeholder => None,
+        Expression::BoolLiteral { location, value } => Some(Operand::Constant {
+            location: *location,
+            ty: Type::Bool,
+            value: Constant::Boolean { value: *value },
+        }),
+        Expression::StringLiteral { location, value } => Some(Operand::Constant {
+            location: *location,
+            ty: Type::String,
+            value: Constant::String {
+                value: value.to_string(),
+            },
+        }),
         Expression::NumberLiteral {
-            location: _,
-            ty: _,
-            value: _,
-        } => expr.clone(),
-        Expression::BytesLiteral { .. } => Expression::Placeholder,
+            location,
+            ty,
+            value,
+        } => Some(Operand::Constant {
+            location: *location,
+            ty: ty.clone(),
+            value: Constant::Integer {
+                value: value.clone(),
+            },
+        }),
+        Expression::FloatLiteral {
+            location,
+            ty,
+            value,
+        } => Some(Operand::Constant {
+            location: *location,
+            ty: ty.clone(),
+            value: Constant::Float { value: *value },
+        }),
+        Expression::BytesLiteral { .. } => None,
+        Expression::InternalFunction { .. } => None,
+        Expression::Binary {
+            location,
+            op,
+            left,
+            right,
+            ..
+        } if *op == BinOpKind::And || *op == BinOpKind::Or => {
+            short_circuit(location, *op, left, right, cfg, vartab, ns)
+        }
+        Expression::Binary {
+            location,
+            ty,
+            op,
+            left,
+            right,
+        } => {
+            let left = expression_cfg(left, cfg, vartab, ns)?;
+            let right = expression_cfg(right, cfg, vartab, ns)?;
+            let res = vartab.temp(*location, ty.clone());
+
+            cfg.emit(ExprKind::BinaryOp {
+                location: *location,
+                res,
+                op: *op,
+                left,
+                right,
+            });
+
+            Some(temp_operand(*location, ty, res))
+        }
+        Expression::Unary {
+            location,
+            ty,
+            op,
+            expr,
+        } => {
+            let value = expression_cfg(expr, cfg, vartab, ns)?;
+            let res = vartab.temp(*location, ty.clone());
+
+            cfg.emit(ExprKind::UnaryOp {
+                location: *location,
+                res,
+                op: *op,
+                value,
+            });
+
+            Some(temp_operand(*location, ty, res))
+        }
+        Expression::PostIncrement {
+            location,
+            ty,
+            var_no,
+        } => Some(post_unary(
+            location,
+            ty,
+            *var_no,
+            BinOpKind::Add,
+            cfg,
+            vartab,
+        )),
+        Expression::PostDecrement {
+            location,
+            ty,
+            var_no,
+        } => Some(post_unary(
+            location,
+            ty,
+            *var_no,
+            BinOpKind::Sub,
+            cfg,
+            vartab,
+        )),
+        Expression::Cast { location, to, expr } => {
+            let value = expression_cfg(expr, cfg, vartab, ns)?;
+            let res = vartab.temp(*location, to.clone());
+
+            cfg.emit(ExprKind::Cast {
+                location: *location,
+                res,
+                value,
+                to: to.clone(),
+            });
+
+            Some(temp_operand(*location, to, res))
+        }
+        Expression::StructLiteral {
+            location,
+            ty,
+            values,
+        } => {
+            let values = values
+                .iter()
+                .map(|value| expression_cfg(value, cfg, vartab, ns))
+                .collect::<Option<Vec<Operand>>>()?;
+            let res = vartab.temp(*location, ty.clone());
+
+            cfg.emit(ExprKind::StructLiteral {
+                location: *location,
+                res,
+                ty: ty.clone(),
+                values,
+            });
+
+            Some(temp_operand(*location, ty, res))
+        }
+        Expression::StructMember {
+            location,
+            ty,
+            expr,
+            field,
+        } => {
+            let value = expression_cfg(expr, cfg, vartab, ns)?;
+            let res = vartab.temp(*location, ty.clone());
+
+            cfg.emit(ExprKind::StructMember {
+                location: *location,
+                res,
+                value,
+                field: *field,
+            });
+
+            Some(temp_operand(*location, ty, res))
+        }
         Expression::InternalFunctionCall {
-            location: _,
+            location,
+            returns,
             function: fun,
-            args: _,
+            args,
         } => {
-            match &**fun {
-                Expression::Variable {
-                    location: _,
-                    ty: _,
-                    value,
-                } => {
-                    cfg.emit(ExprKind::Call {
-                        value: value.to_string(),
-                    });
-                }
-                _ => {}
-            }
-            Expression::Placeholder
+            let function_no = match &**fun {
+                Expression::InternalFunction { function_no, .. } => *function_no,
+                _ => return None,
+            };
+
+            let method = ns.functions[function_no].is_method();
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(arg_no, arg)| match arg_no {
+                    0 if method => receiver_cfg(location, arg, cfg, vartab, ns),
+                    _ => expression_cfg(arg, cfg, vartab, ns),
+                })
+                .collect::<Option<Vec<Operand>>>()?;
+
+            // the first return value, more are not supported yet
+            let res = returns
+                .first()
+                .map(|ty| (vartab.temp(*location, ty.clone()), ty));
+
+            cfg.emit(ExprKind::Call {
+                location: *location,
+                value: ns.functions[function_no].symbol_name(),
+                args,
+                res: res.map(|(res, _)| res),
+            });
+
+            res.map(|(res, ty)| temp_operand(*location, ty, res))
         }
         Expression::Builtin {
-            location: _,
-            types: _,
+            location,
+            types,
             builtin,
             args,
         } => match builtin {
-            Builtin::Assert => Expression::Placeholder,
-            Builtin::Print => {
-                let expr = expression_cfg(&args[0], cfg, ns);
-                let mut val = "".to_string();
-                match expr {
-                    Expression::StringLiteral { location: _, value } => {
-                        val = value;
-                    }
-                    Expression::NumberLiteral {
-                        location: _,
-                        ty: _,
-                        value,
-                    } => {
-                        val = value.to_string();
-                    }
-                    _ => {}
-                }
-                cfg.emit(ExprKind::Print { value: val });
-                Expression::Placeholder
+            Builtin::Assert => {
+                let cond = expression_cfg(&args[0], cfg, vartab, ns)?;
+
+                let failed = cfg.new_basic_block("assert_failed");
+                let passed = cfg.new_basic_block("assert_passed");
+                cfg.terminate(TerminatorKind::Branch {
+                    cond,
+                    true_block: passed,
+                    false_block: failed,
+                });
+
+                // the message is only evaluated when the assertion fails
+                cfg.set_basic_block(failed);
+                let message = args
+                    .get(1)
+                    .and_then(|message| expression_cfg(message, cfg, vartab, ns));
+                cfg.emit(ExprKind::AssertFailure {
+                    location: *location,
+                    message,
+                });
+                cfg.terminate(TerminatorKind::Unreachable);
+
+                cfg.set_basic_block(passed);
+                None
+            }
+            Builtin::Print | Builtin::Println => {
+                let value = expression_cfg(&args[0], cfg, vartab, ns)?;
+                cfg.emit(ExprKind::Print {
+                    location: *location,
+                    value,
+                    newline: *builtin == Builtin::Println,
+                });
+                None
+            }
+            Builtin::Host(builtin_no) => {
+                let args = args
+                    .iter()
+                    .map(|arg| expression_cfg(arg, cfg, vartab, ns))
+                    .collect::<Option<Vec<Operand>>>()?;
+
+                let res = types
+                    .first()
+                    .filter(|ty| **ty != Type::Void)
+                    .map(|ty| (vartab.temp(*location, ty.clone()), ty));
+
+                cfg.emit(ExprKind::Call {
+                    location: *location,
+                    value: ns.builtins[*builtin_no].symbol_name(*builtin_no),
+                    args,
+                    res: res.map(|(res, _)| res),
+                });
+
+                res.map(|(res, ty)| temp_operand(*location, ty, res))
             }
         },
         Expression::Variable {
-            location: _,
-            ty: _,
-            value: _,
-        } => expr.clone(),
+            location,
+            ty,
+            var_no,
+        } => Some(Operand::Load {
+            location: *location,
+            ty: ty.clone(),
+            var_no: *var_no,
+        }),
+    }
+}
+
+/// The address of the value a method is called on, so the method can assign its fields.
+/// A value not held by a variable is stored into a temporary first.
+fn receiver_cfg(
+    location: &Loc,
+    receiver: &Expression,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut VariableTable,
+    ns: &mut Namespace,
+) -> Option<Operand> {
+    let ty = receiver.ty();
+    let (var_no, fields) = match place(receiver) {
+        Some(place) => place,
+        None => {
+            let value = expression_cfg(receiver, cfg, vartab, ns)?;
+            let temp = vartab.temp(*location, ty.clone());
+            cfg.emit(ExprKind::Store {
+                location: *location,
+                var_no: temp,
+                value,
+            });
+            (temp, vec![])
+        }
+    };
+
+    Some(Operand::Address {
+        location: *location,
+        ty,
+        var_no,
+        fields,
+    })
+}
+
+/// `x++` and `x--` store the updated variable, the value is the one before the update
+fn post_unary(
+    location: &Loc,
+    ty: &Type,
+    var_no: usize,
+    op: BinOpKind,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut VariableTable,
+) -> Operand {
+    let old = vartab.temp(*location, ty.clone());
+    cfg.emit(ExprKind::Store {
+        location: *location,
+        var_no: old,
+        value: temp_operand(*location, ty, var_no),
+    });
+
+    let res = vartab.temp(*location, ty.clone());
+    cfg.emit(ExprKind::BinaryOp {
+        location: *location,
+        res,
+        op,
+        left: temp_operand(*location, ty, old),
+        right: Operand::Constant {
+            location: *location,
+            ty: ty.clone(),
+            value: Constant::Integer {
+                value: BigInt::from(1),
+            },
+        },
+    });
+    cfg.emit(ExprKind::Store {
+        location: *location,
+        var_no,
+        value: temp_operand(*location, ty, res),
+    });
+
+    temp_operand(*location, ty, old)
+}
+
+/// `&&` and `||` evaluate the right operand only if the left one does not decide the result
+fn short_circuit(
+    location: &Loc,
+    op: BinOpKind,
+    left: &Expression,
+    right: &Expression,
+    cfg: &mut ControlFlowGraph,
+    vartab: &mut VariableTable,
+    ns: &mut Namespace,
+) -> Option<Operand> {
+    let left = expression_cfg(left, cfg, vartab, ns)?;
+    let res = vartab.temp(*location, Type::Bool);
+
+    cfg.emit(ExprKind::Store {
+        location: *location,
+        var_no: res,
+        value: left.clone(),
+    });
+
+    let name = if op == BinOpKind::And { "and" } else { "or" };
+    let rhs = cfg.new_basic_block(&format!("{}_rhs", name));
+    let end = cfg.new_basic_block(&format!("{}_end", name));
+
+    let (true_block, false_block) = if op == BinOpKind::And {
+        (rhs, end)
+    } else {
+        (end, rhs)
+    };
+
+    cfg.terminate(TerminatorKind::Branch {
+        cond: left,
+        true_block,
+        false_block,
+    });
+
+    cfg.set_basic_block(rhs);
+    if let Some(right) = expression_cfg(right, cfg, vartab, ns) {
+        cfg.emit(ExprKind::Store {
+            location: *location,
+            var_no: res,
+            value: right,
+        });
+    }
+    cfg.terminate(TerminatorKind::Goto { block: end });
+
+    cfg.set_basic_block(end);
+
+    Some(temp_operand(*location, &Type::Bool, res))
+}
+
+fn temp_operand(location: Loc, ty: &Type, var_no: usize) -> Operand {
+    Operand::Load {
+        location,
+        ty: ty.clone(),
+        var_no,
     }
 }
@@ -1 +1,34 @@
-pub struct VariableTable {}
+use dc_hir::{Type, Variable};
+use dc_lexer::Loc;
+
+/// The variables of a function while it is lowered: the declared variables keep the number
+/// given by the symbol table, temporaries are numbered after them.
+#[derive(Clone, Debug, Default)]
+pub struct VariableTable {
+    vars: Vec<Variable>,
+}
+
+impl VariableTable {
+    pub fn new(vars: &[Variable]) -> Self {
+        VariableTable {
+            vars: vars.to_vec(),
+        }
+    }
+
+    /// Add a temporary for an intermediate value, returns its number
+    pub fn temp(&mut self, location: Loc, ty: Type) -> usize {
+        let var_no = self.vars.len();
+
+        self.vars.push(Variable {
+            location,
+            name: format!("temp.{}", var_no),
+            ty,
+        });
+
+        var_no
+    }
+
+    pub fn into_vars(self) -> Vec<Variable> {
+        self.vars
+    }
+}
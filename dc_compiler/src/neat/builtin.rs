@@ -1,72 +1,146 @@
 use crate::symbol_table::SymbolTable;
 use crate::{expression, Namespace};
 use dc_hir::{Builtin, Expression, Type};
-use dc_lexer::Location;
+use dc_lexer::{Diagnostic, Loc};
 
 #[derive(PartialEq, Clone, Debug)]
 pub struct Prototype {
     pub builtin: Builtin,
-    pub namespace: Option<&'static str>,
+    /// the package the builtin can also be called from, like `fmt.println`
+    pub package: Option<&'static str>,
     pub name: &'static str,
-    pub args: &'static [Type],
+    /// the types each argument accepts
+    pub args: &'static [&'static [Type]],
     pub ret: &'static [Type],
     pub doc: &'static str,
 }
 
-// A list of all Solidity builtins functions
-static BUILTIN_FUNCTIONS: [Prototype; 3] = [
+/// A builtin provided by the program embedding the compiler, registered with
+/// `Namespace::add_builtin` before the sourcecode is resolved. It is called by its name,
+/// like `print`.
+#[derive(PartialEq, Clone, Debug)]
+pub struct HostFunction {
+    pub name: String,
+    pub args: Vec<Type>,
+    pub ret: Vec<Type>,
+    pub doc: String,
+    pub lowering: HostLowering,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum HostLowering {
+    /// call an external function by its symbol, like `abs` of the C library
+    Symbol(String),
+    /// call a function of the embedding program at this address, like an `extern "C" fn`;
+    /// the JIT maps it to the declared function
+    Callback(usize),
+}
+
+impl HostFunction {
+    /// The name of the declared function in the module, `builtin_no` is its registration number
+    pub fn symbol_name(&self, builtin_no: usize) -> String {
+        match &self.lowering {
+            HostLowering::Symbol(symbol) => symbol.clone(),
+            HostLowering::Callback(_) => format!("dc.host.{}.{}", self.name, builtin_no),
+        }
+    }
+}
+
+/// The types `print` and `println` format, the widest of each kind
+const PRINTABLE: &[Type] = &[
+    Type::String,
+    Type::Int(256),
+    Type::Uint(256),
+    Type::Float(64),
+    Type::Bool,
+];
+
+// A list of all builtins functions, a name may be overloaded for different argument counts
+static BUILTIN_FUNCTIONS: [Prototype; 4] = [
     Prototype {
         builtin: Builtin::Print,
-        namespace: None,
+        package: Some("fmt"),
         name: "print",
-        args: &[Type::String],
+        args: &[PRINTABLE],
         ret: &[Type::Void],
-        doc: "log string without new line",
+        doc: "log a value without new line",
     },
     Prototype {
-        builtin: Builtin::Print,
-        namespace: None,
+        builtin: Builtin::Println,
+        package: Some("fmt"),
         name: "println",
-        args: &[Type::String],
+        args: &[PRINTABLE],
         ret: &[Type::Void],
-        doc: "log string with line",
+        doc: "log a value with new line",
     },
     Prototype {
         builtin: Builtin::Assert,
-        namespace: None,
+        package: None,
         name: "assert",
-        args: &[Type::Bool],
+        args: &[&[Type::Bool]],
         ret: &[Type::Void],
         doc: "abort execution if argument evaluates to false",
     },
+    Prototype {
+        builtin: Builtin::Assert,
+        package: None,
+        name: "assert",
+        args: &[&[Type::Bool], &[Type::String]],
+        ret: &[Type::Void],
+        doc: "abort execution with the message if the first argument evaluates to false",
+    },
 ];
 
 #[derive(Clone, PartialEq)]
 pub enum Symbol {
-    Function(Vec<Location>),
-    Variable(Location, usize, usize),
-    Struct(Location, usize),
-    Import(Location, usize),
+    Function(Vec<Loc>),
+    Variable(Loc, usize, usize),
+    Struct(Loc, usize),
+    Import(Loc, usize),
 }
 
-pub fn is_builtin_call(namespace: Option<&str>, fname: &str) -> bool {
+pub fn is_builtin_call(ns: &Namespace, namespace: Option<&str>, fname: &str) -> bool {
     BUILTIN_FUNCTIONS
         .iter()
-        .any(|p| p.name == fname && p.namespace == namespace)
+        .any(|p| p.name == fname && callable_from(p, namespace))
+        || (namespace.is_none() && ns.builtins.iter().any(|host| host.name == fname))
+}
+
+/// A builtin is called unqualified, or qualified by its package
+fn callable_from(prototype: &Prototype, namespace: Option<&str>) -> bool {
+    namespace.is_none() || prototype.package == namespace
 }
 
 pub fn resolve_call(
-    location: &Location,
+    location: &Loc,
     namespace: Option<&str>,
     ns: &mut Namespace,
     id: &str,
     args: &Vec<dc_parser::Argument>, // args: &[Expression],
     symbol_table: &mut SymbolTable,
 ) -> Result<Expression, ()> {
-    let matches = BUILTIN_FUNCTIONS
+    // the builtins of the language come first, then the ones of the embedding program
+    let mut matches = BUILTIN_FUNCTIONS
         .iter()
-        .filter(|p| p.name == id && p.namespace == namespace)
-        .collect::<Vec<&Prototype>>();
+        .filter(|p| p.name == id && callable_from(p, namespace))
+        .map(|p| {
+            let args = p.args.iter().map(|types| types.to_vec()).collect();
+            (p.builtin.clone(), args, p.ret.to_vec())
+        })
+        .collect::<Vec<(Builtin, Vec<Vec<Type>>, Vec<Type>)>>();
+
+    if namespace.is_none() {
+        matches.extend(
+            ns.builtins
+                .iter()
+                .enumerate()
+                .filter(|(_, host)| host.name == id)
+                .map(|(no, host)| {
+                    let args = host.args.iter().map(|ty| vec![ty.clone()]).collect();
+                    (Builtin::Host(no), args, host.ret.clone())
+                }),
+        );
+    }
 
     let mut resolved_args = Vec::new();
     for arg in args {
@@ -74,44 +148,96 @@ pub fn resolve_call(
         resolved_args.push(expr);
     }
 
-    for func in &matches {
-        if func.args.len() != args.len() {
+    let mut same_arity = false;
+    for (builtin, params, ret) in &matches {
+        if params.len() != args.len() {
             continue;
         }
-        let matches = true;
+        same_arity = true;
+
+        let accepted = params
+            .iter()
+            .zip(&resolved_args)
+            .map(|(types, arg)| {
+                // a number literal fits an integer type of any sign
+                types.iter().find(|ty| {
+                    let arg = expression::implicit_conversion(arg.clone(), ty);
+                    arg.ty().can_convert_to(ty)
+                })
+            })
+            .collect::<Option<Vec<&Type>>>();
+
+        if let Some(accepted) = accepted {
+            // the host function is compiled already, its arguments must have the exact types
+            let args = match builtin {
+                Builtin::Host(_) => accepted
+                    .into_iter()
+                    .zip(resolved_args)
+                    .map(|(ty, arg)| expression::implicit_conversion(arg, ty))
+                    .collect(),
+                _ => resolved_args,
+            };
 
-        if matches {
             return Ok(Expression::Builtin {
                 location: location.to_owned(),
-                types: func.ret.to_vec(),
-                builtin: func.builtin.clone(),
-                args: resolved_args,
+                types: ret.clone(),
+                builtin: builtin.clone(),
+                args,
             });
         }
     }
 
+    if same_arity {
+        let types = resolved_args
+            .iter()
+            .map(|arg| format!("‘{}’", arg.ty()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        ns.diagnostics.push(Diagnostic::type_error(
+            *location,
+            format!(
+                "builtin function ‘{}’ cannot be called with arguments of type {}",
+                id, types
+            ),
+        ));
+        return Err(());
+    }
+
+    ns.diagnostics.push(Diagnostic::error(
+        *location,
+        format!(
+            "builtin function ‘{}’ expects {} arguments, {} provided",
+            id,
+            matches[0].1.len(),
+            args.len()
+        ),
+    ));
+
     Err(())
 }
 
 #[cfg(test)]
 mod tests {
     use crate::builtin::is_builtin_call;
-    use dc_lexer::Location;
+    use crate::Namespace;
+    use dc_lexer::Loc;
     use dc_parser::parse_tree::{Expression, ExpressionType};
 
     #[test]
     fn should_identify_builtin_print() {
-        let is_builtin = is_builtin_call(None, "print");
+        let ns = Namespace::new();
+        let is_builtin = is_builtin_call(&ns, None, "print");
         assert_eq!(true, is_builtin);
 
-        let no_builtin = is_builtin_call(None, "printf");
+        let no_builtin = is_builtin_call(&ns, None, "printf");
         assert_eq!(false, no_builtin);
     }
 
     #[test]
     fn should_resolved_call() {
         let expr = Expression {
-            location: Location::new(0, 0),
+            location: Loc(0, 0, 0),
             node: ExpressionType::String {
                 value: "hello,world".to_string(),
             },
@@ -0,0 +1,107 @@
+use crate::neat::Namespace;
+use dc_hir::{Expression, Statement, Type};
+use dc_lexer::{Diagnostic, Loc};
+
+/// The value a constructor creates, its local variable `self` follows the parameters.
+pub fn constructed(function_no: usize, location: Loc, ns: &Namespace) -> Expression {
+    let function = &ns.functions[function_no];
+
+    Expression::Variable {
+        location,
+        ty: function.returns[0].ty.clone(),
+        var_no: function.params.len(),
+    }
+}
+
+/// Every field of the new value must be assigned before the constructor returns, on each path.
+pub fn check_initialized(function_no: usize, ns: &mut Namespace) {
+    let function = &ns.functions[function_no];
+    let struct_no = match function.returns[0].ty {
+        Type::Struct { struct_no, .. } => struct_no,
+        _ => return,
+    };
+
+    let decl = &ns.structs[struct_no];
+    let mut missing = vec![false; decl.fields.len()];
+    let initialized = vec![false; decl.fields.len()];
+    assigned(
+        &function.body,
+        function.params.len(),
+        initialized,
+        &mut missing,
+    );
+
+    let names = decl
+        .fields
+        .iter()
+        .zip(missing)
+        .filter(|(_, missing)| *missing)
+        .map(|(field, _)| format!("‘{}’", field.name))
+        .collect::<Vec<String>>();
+
+    if !names.is_empty() {
+        let message = format!(
+            "constructor of ‘{}’ leaves field(s) {} uninitialized",
+            decl.name,
+            names.join(", ")
+        );
+        let location = function.location;
+        ns.diagnostics.push(Diagnostic::error(location, message));
+    }
+}
+
+/// Follow the statements with the fields assigned so far, returns the assigned fields
+/// after them, or `None` when control does not reach their end. A return marks the fields
+/// not assigned yet as missing. Loop bodies may not run, so they assign nothing.
+fn assigned(
+    body: &[Statement],
+    self_no: usize,
+    mut initialized: Vec<bool>,
+    missing: &mut Vec<bool>,
+) -> Option<Vec<bool>> {
+    for stmt in body {
+        match stmt {
+            Statement::Assign { var_no, .. } if *var_no == self_no => {
+                initialized.iter_mut().for_each(|field| *field = true);
+            }
+            Statement::AssignField { var_no, fields, .. }
+                if *var_no == self_no && fields.len() == 1 =>
+            {
+                initialized[fields[0]] = true;
+            }
+            Statement::Return { .. } => {
+                for (missing, initialized) in missing.iter_mut().zip(&initialized) {
+                    *missing |= !initialized;
+                }
+                return None;
+            }
+            Statement::Break { .. } | Statement::Continue { .. } => return None,
+            Statement::If {
+                then, otherwise, ..
+            } => {
+                let then = assigned(then, self_no, initialized.clone(), missing);
+                let otherwise = assigned(otherwise, self_no, initialized.clone(), missing);
+
+                initialized = match (then, otherwise) {
+                    (Some(then), Some(otherwise)) => then
+                        .iter()
+                        .zip(otherwise)
+                        .map(|(then, otherwise)| *then && otherwise)
+                        .collect(),
+                    (Some(branch), None) | (None, Some(branch)) => branch,
+                    (None, None) => return None,
+                };
+            }
+            Statement::For { init, body, .. } => {
+                initialized = assigned(init, self_no, initialized, missing)?;
+                assigned(body, self_no, initialized.clone(), missing);
+            }
+            Statement::While { body, .. } | Statement::Loop { body, .. } => {
+                assigned(body, self_no, initialized.clone(), missing);
+            }
+            _ => {}
+        }
+    }
+
+    Some(initialized)
+}
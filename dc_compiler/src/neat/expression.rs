@@ -1,8 +1,13 @@
-use dc_hir::{Expression, Type};
-use dc_parser::{Argument, ExpressionType};
+use dc_hir::{BinOpKind, Expression, Type, UnOpKind};
+use dc_lexer::{Diagnostic, Loc};
+use dc_parser::{
+    AffixesUnaryOperator, Argument, BooleanOperator, Comparison, ExpressionType, Identifier,
+    Operator, UnaryOperator,
+};
+use num_bigint::{BigInt, Sign};
 
 use crate::builtin;
-use crate::neat::Namespace;
+use crate::neat::{is_unresolved, Namespace};
 use crate::symbol_table::SymbolTable;
 
 pub fn expression(
@@ -11,51 +16,952 @@ pub fn expression(
     symbol_table: &mut SymbolTable,
 ) -> Result<dc_hir::Expression, ()> {
     match &expr.node {
-        ExpressionType::Range { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::BoolOp { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::Binop { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::Unop { .. } => Ok(dc_hir::Expression::Placeholder),
+        ExpressionType::Range { .. } => {
+            ns.diagnostics.push(Diagnostic::error(
+                expr.location,
+                "a range can only be iterated by a for loop".to_string(),
+            ));
+            Err(())
+        }
+        ExpressionType::BoolOp { op, values } => bool_op(expr, op, values, ns, symbol_table),
+        ExpressionType::Binop { a, op, b } => binary(expr, a, op, b, ns, symbol_table),
+        ExpressionType::Unop { op, a } => unary(expr, op, a, ns, symbol_table),
         ExpressionType::String { value } => Ok(dc_hir::Expression::StringLiteral {
             location: *&expr.location,
             value: value.to_string(),
         }),
-        ExpressionType::Bool { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::Number { value } => {
-            let bits = value.bits();
-            let int_size = if bits < 7 { 8 } else { (bits + 7) & !7 } as u16;
-
-            Ok(dc_hir::Expression::NumberLiteral {
-                location: *&expr.location,
-                ty: Type::Int(int_size),
-                value: value.clone(),
-            })
-        }
-        ExpressionType::List { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::Identifier { id: name } => Ok(dc_hir::Expression::Variable {
-            location: *&expr.location,
-            ty: Type::String,
-            value: name.name.clone(),
+        ExpressionType::Bool { value } => Ok(dc_hir::Expression::BoolLiteral {
+            location: expr.location,
+            value: *value,
         }),
-        ExpressionType::Type { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::MemberAccess { .. } => Ok(dc_hir::Expression::Placeholder),
+        ExpressionType::Number { value } => number_literal(expr.location, value.clone(), ns),
+        ExpressionType::Float { value } => float_literal(expr.location, *value, ns),
+        ExpressionType::List { .. } => unsupported(expr, "list", ns),
+        ExpressionType::Identifier { id } => match symbol_table.find(&id.name) {
+            Some(var_no) => Ok(dc_hir::Expression::Variable {
+                location: expr.location,
+                ty: symbol_table.vars[var_no].ty.clone(),
+                var_no,
+            }),
+            None => {
+                ns.diagnostics.push(Diagnostic::decl_error(
+                    id.loc,
+                    format!("‘{}’ is not found", id.name),
+                ));
+                Err(())
+            }
+        },
+        ExpressionType::Type { .. } => unsupported(expr, "type", ns),
+        ExpressionType::MemberAccess { value, name } => {
+            member_access(expr, value, name, ns, symbol_table)
+        }
+        ExpressionType::StructLiteral { name, fields } => {
+            struct_literal(expr, name, fields, ns, symbol_table)
+        }
         ExpressionType::Call { function, args } => {
             let result = function_call_expr(function, args, ns, symbol_table);
             return result;
         }
-        ExpressionType::Compare { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::PostUnop { .. } => Ok(dc_hir::Expression::Placeholder),
-        ExpressionType::EmptyObject => Ok(dc_hir::Expression::Placeholder),
+        ExpressionType::Compare { op, left, right } => {
+            compare(expr, op, left, right, ns, symbol_table)
+        }
+        ExpressionType::PostUnop { op, a } => post_unary(expr, op, a, ns, symbol_table),
+        ExpressionType::EmptyObject => unsupported(expr, "object literal", ns),
+    }
+}
+
+/// A number literal has the narrowest signed type it fits in, see `literal_type`
+fn number_literal(location: Loc, value: BigInt, ns: &mut Namespace) -> Result<Expression, ()> {
+    match literal_type(&value) {
+        Some(ty) => Ok(Expression::NumberLiteral {
+            location,
+            ty,
+            value,
+        }),
+        None => {
+            let ty = if value.sign() == Sign::Minus {
+                Type::Int(256)
+            } else {
+                Type::Uint(256)
+            };
+            ns.diagnostics.push(Diagnostic::type_error(
+                location,
+                format!("number literal ‘{}’ does not fit in ‘{}’", value, ty),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// A float literal is a `float64`, unless it is used as a `float32`
+fn float_literal(location: Loc, value: f64, ns: &mut Namespace) -> Result<Expression, ()> {
+    if value.is_finite() {
+        return Ok(Expression::FloatLiteral {
+            location,
+            ty: Type::Float(64),
+            value,
+        });
+    }
+
+    ns.diagnostics.push(Diagnostic::type_error(
+        location,
+        "float literal does not fit in ‘float64’".to_string(),
+    ));
+    Err(())
+}
+
+/// The narrowest signed integer type of a number literal, a multiple of 8 bits wide. A value
+/// beyond `int256` can still be a `uint256`, `None` if it is out of that range too.
+pub fn literal_type(value: &BigInt) -> Option<Type> {
+    // -128 needs as many bits as 127, both fit in `int8`
+    let magnitude = if value.sign() == Sign::Minus {
+        -value - BigInt::from(1)
+    } else {
+        value.clone()
+    };
+    let bits = magnitude.bits() + 1;
+
+    if bits <= 256 {
+        Some(Type::Int(((bits + 7) & !7) as u16))
+    } else if value.sign() != Sign::Minus && value.bits() <= 256 {
+        Some(Type::Uint(256))
+    } else {
+        None
+    }
+}
+
+/// Whether a number literal is in the range of the integer type, or exact as a float
+pub fn literal_fits(value: &BigInt, ty: &Type) -> bool {
+    match ty {
+        Type::Int(bits) => {
+            matches!(literal_type(value), Some(Type::Int(needed)) if needed <= *bits)
+        }
+        Type::Uint(bits) => value.sign() != Sign::Minus && value.bits() <= *bits as u64,
+        // the bits of the mantissa
+        Type::Float(32) => value.bits() <= 24,
+        Type::Float(_) => value.bits() <= 53,
+        _ => false,
+    }
+}
+
+/// Whether the expression is a literal which can take the type
+fn literal_takes(expr: &Expression, ty: &Type) -> bool {
+    match expr {
+        Expression::NumberLiteral { value, .. } => literal_fits(value, ty),
+        Expression::FloatLiteral { value, .. } => float_fits(*value, ty),
+        _ => false,
+    }
+}
+
+/// Whether a float literal is in the range of the float type, it may be rounded
+fn float_fits(value: f64, ty: &Type) -> bool {
+    match ty {
+        Type::Float(32) => (value as f32).is_finite(),
+        Type::Float(_) => true,
+        _ => false,
+    }
+}
+
+/// The number type both operands are converted to: a literal takes the type of the other
+/// operand if it fits, else it is the type the other one widens to. `None` when neither
+/// widens to the other, like `int8` and `uint8`, or `int8` and `float32`.
+pub fn common_type(left: &Expression, right: &Expression) -> Option<Type> {
+    let (l, r) = (left.ty(), right.ty());
+    if !l.is_number() || !r.is_number() {
+        return None;
+    }
+
+    match (left, right) {
+        _ if literal_takes(left, &r) => Some(r),
+        _ if literal_takes(right, &l) => Some(l),
+        _ if l.can_convert_to(&r) => Some(r),
+        _ if r.can_convert_to(&l) => Some(l),
+        _ => None,
+    }
+}
+
+/// Give a value the number type it is used as: literals which fit are retyped, arithmetic
+/// is done in the wider type and anything else narrower is extended. Other mismatches are
+/// left to the type checker.
+pub fn implicit_conversion(expr: Expression, to: &Type) -> Expression {
+    match (expr, to) {
+        (
+            Expression::NumberLiteral {
+                location, value, ..
+            },
+            Type::Float(_),
+        ) if literal_fits(&value, to) => Expression::FloatLiteral {
+            location,
+            ty: to.clone(),
+            // exact, as it fits the mantissa
+            value: value.to_string().parse().unwrap_or_default(),
+        },
+        (
+            Expression::FloatLiteral {
+                location, value, ..
+            },
+            to,
+        ) if float_fits(value, to) => Expression::FloatLiteral {
+            location,
+            ty: to.clone(),
+            value,
+        },
+        (
+            Expression::NumberLiteral {
+                location, value, ..
+            },
+            to,
+        ) if literal_fits(&value, to) => Expression::NumberLiteral {
+            location,
+            ty: to.clone(),
+            value,
+        },
+        (
+            Expression::Binary {
+                location,
+                ty,
+                op,
+                left,
+                right,
+            },
+            to,
+        ) if ty.is_number() && ty != *to && ty.can_convert_to(to) => Expression::Binary {
+            location,
+            ty: to.clone(),
+            op,
+            left: Box::new(implicit_conversion(*left, to)),
+            right: Box::new(implicit_conversion(*right, to)),
+        },
+        (
+            Expression::Unary {
+                location,
+                ty: Type::Int(bits),
+                op,
+                expr,
+            },
+            Type::Int(to_bits),
+        ) if bits < *to_bits => Expression::Unary {
+            location,
+            ty: to.clone(),
+            op,
+            expr: Box::new(implicit_conversion(*expr, to)),
+        },
+        (expr, to) if to.is_number() => match (expr.ty(), expr.location()) {
+            (ty, Some(location)) if ty.is_number() && ty != *to && ty.can_convert_to(to) => {
+                Expression::Cast {
+                    location,
+                    to: to.clone(),
+                    expr: Box::new(expr),
+                }
+            }
+            _ => expr,
+        },
+        (expr, _) => expr,
+    }
+}
+
+/// `uint8(value)` or `float64(value)`, a number converted to another number type. An integer
+/// is truncated to a narrower one and extended by its sign to a wider one, a float is rounded
+/// to a float and truncated toward zero to an integer. A number literal must fit an integer type.
+fn explicit_conversion(
+    location: &Loc,
+    to: Type,
+    args: &[Argument],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    if args.len() != 1 {
+        ns.diagnostics.push(Diagnostic::type_error(
+            *location,
+            format!(
+                "conversion to ‘{}’ expects 1 argument, {} provided",
+                to,
+                args.len()
+            ),
+        ));
+        return Err(());
+    }
+
+    let value = expression(&args[0].expr, ns, symbol_table)?;
+    let from = value.ty();
+
+    match value {
+        Expression::NumberLiteral {
+            location, value, ..
+        } if to.is_integer() => {
+            if literal_fits(&value, &to) {
+                return Ok(Expression::NumberLiteral {
+                    location,
+                    ty: to,
+                    value,
+                });
+            }
+
+            ns.diagnostics.push(Diagnostic::type_error(
+                location,
+                format!("number literal ‘{}’ does not fit in ‘{}’", value, to),
+            ));
+            Err(())
+        }
+        value if from == to => Ok(value),
+        value if from.is_number() && to.is_number() => Ok(Expression::Cast {
+            location: *location,
+            to,
+            expr: Box::new(value),
+        }),
+        value if unresolved(&value) => Err(()),
+        _ => {
+            ns.diagnostics.push(Diagnostic::type_error(
+                *location,
+                format!("cannot convert ‘{}’ to ‘{}’", from, to),
+            ));
+            Err(())
+        }
+    }
+}
+
+fn binary(
+    expr: &dc_parser::Expression,
+    a: &dc_parser::Expression,
+    op: &Operator,
+    b: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let (left, right) = operands(a, b, ns, symbol_table)?;
+
+    arithmetic(expr.location, op, left, right, ns)
+}
+
+/// Arithmetic operators take numbers, bitwise and shift operators integers. The narrower
+/// operand is converted to the type of the wider one.
+pub fn arithmetic(
+    location: Loc,
+    op: &Operator,
+    left: Expression,
+    right: Expression,
+    ns: &mut Namespace,
+) -> Result<Expression, ()> {
+    let (op, symbol) = match op {
+        Operator::Add => (BinOpKind::Add, "+"),
+        Operator::Sub => (BinOpKind::Sub, "-"),
+        Operator::Multiply => (BinOpKind::Mul, "*"),
+        Operator::Divide => (BinOpKind::Div, "/"),
+        Operator::Modulo => (BinOpKind::Rem, "%"),
+        Operator::LShift => (BinOpKind::Shl, "<<"),
+        Operator::RShift => (BinOpKind::Shr, ">>"),
+        Operator::BitOr => (BinOpKind::BitOr, "|"),
+        Operator::BitXor => (BinOpKind::BitXor, "^"),
+        Operator::BitAnd => (BinOpKind::BitAnd, "&"),
+        _ => {
+            ns.diagnostics.push(Diagnostic::error(
+                location,
+                "binary operator expression is not supported yet".to_string(),
+            ));
+            return Err(());
+        }
+    };
+
+    let bitwise = matches!(
+        op,
+        BinOpKind::Shl | BinOpKind::Shr | BinOpKind::BitOr | BinOpKind::BitXor | BinOpKind::BitAnd
+    );
+    let ty = match common_type(&left, &right) {
+        Some(ty) if !(bitwise && ty.is_float()) => ty,
+        _ => return mismatched_operands(location, symbol, &left, &right, ns),
+    };
+
+    Ok(Expression::Binary {
+        location,
+        ty: ty.clone(),
+        op,
+        left: Box::new(implicit_conversion(left, &ty)),
+        right: Box::new(implicit_conversion(right, &ty)),
+    })
+}
+
+/// Integers can be ordered, integers and booleans compared for equality
+fn compare(
+    expr: &dc_parser::Expression,
+    op: &Comparison,
+    left: &dc_parser::Expression,
+    right: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let (op, symbol) = match op {
+        Comparison::Equal => (BinOpKind::Eq, "=="),
+        Comparison::NotEqual => (BinOpKind::Ne, "!="),
+        Comparison::Less => (BinOpKind::Lt, "<"),
+        Comparison::LessOrEqual => (BinOpKind::Le, "<="),
+        Comparison::Greater => (BinOpKind::Gt, ">"),
+        Comparison::GreaterOrEqual => (BinOpKind::Ge, ">="),
+        _ => return unsupported(expr, "comparison", ns),
+    };
+
+    let (left, right) = operands(left, right, ns, symbol_table)?;
+
+    let (left, right) = match (left.ty(), right.ty()) {
+        (Type::Bool, Type::Bool) if op == BinOpKind::Eq || op == BinOpKind::Ne => (left, right),
+        _ => match common_type(&left, &right) {
+            Some(ty) => (
+                implicit_conversion(left, &ty),
+                implicit_conversion(right, &ty),
+            ),
+            None => return mismatched_operands(expr.location, symbol, &left, &right, ns),
+        },
+    };
+
+    Ok(Expression::Binary {
+        location: expr.location,
+        ty: Type::Bool,
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    })
+}
+
+fn bool_op(
+    expr: &dc_parser::Expression,
+    op: &BooleanOperator,
+    values: &[dc_parser::Expression],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let (op, symbol) = match op {
+        BooleanOperator::And => (BinOpKind::And, "&&"),
+        BooleanOperator::Or => (BinOpKind::Or, "||"),
+    };
+
+    let values = values
+        .iter()
+        .map(|value| expression(value, ns, symbol_table))
+        .collect::<Vec<Result<Expression, ()>>>()
+        .into_iter()
+        .collect::<Result<Vec<Expression>, ()>>()?;
+
+    let mut values = values.into_iter();
+    let mut result = values.next().ok_or(())?;
+    for value in values {
+        if result.ty() != Type::Bool || value.ty() != Type::Bool {
+            return mismatched_operands(expr.location, symbol, &result, &value, ns);
+        }
+
+        result = Expression::Binary {
+            location: expr.location,
+            ty: Type::Bool,
+            op,
+            left: Box::new(result),
+            right: Box::new(value),
+        };
+    }
+
+    Ok(result)
+}
+
+fn unary(
+    expr: &dc_parser::Expression,
+    op: &UnaryOperator,
+    a: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let value = expression(a, ns, symbol_table)?;
+    let ty = value.ty();
+
+    // a negative number is a literal of its own, it may need another type
+    match (op, &value) {
+        (UnaryOperator::Neg, Expression::NumberLiteral { value, .. }) => {
+            return number_literal(expr.location, -value, ns);
+        }
+        (UnaryOperator::Neg, Expression::FloatLiteral { ty, value, .. }) => {
+            return Ok(Expression::FloatLiteral {
+                location: expr.location,
+                ty: ty.clone(),
+                value: -value,
+            });
+        }
+        _ => {}
+    }
+
+    match (op, ty) {
+        (UnaryOperator::Pos, ty) if ty.is_number() => Ok(value),
+        (UnaryOperator::Neg, ty) if ty.is_signed_integer() || ty.is_float() => {
+            Ok(Expression::Unary {
+                location: expr.location,
+                ty,
+                op: UnOpKind::Neg,
+                expr: Box::new(value),
+            })
+        }
+        (UnaryOperator::Not, Type::Bool) => Ok(Expression::Unary {
+            location: expr.location,
+            ty: Type::Bool,
+            op: UnOpKind::Not,
+            expr: Box::new(value),
+        }),
+        (UnaryOperator::Inv, ty) if ty.is_integer() => Ok(Expression::Unary {
+            location: expr.location,
+            ty,
+            op: UnOpKind::Not,
+            expr: Box::new(value),
+        }),
+        _ if unresolved(&value) => Err(()),
+        (op, ty) => {
+            let symbol = match op {
+                UnaryOperator::Pos => "+",
+                UnaryOperator::Neg => "-",
+                UnaryOperator::Not => "!",
+                UnaryOperator::Inv => "~",
+            };
+            ns.diagnostics.push(Diagnostic::type_error(
+                expr.location,
+                format!("operator ‘{}’ cannot be applied to ‘{}’", symbol, ty),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// `x++` and `x--` update an integer variable
+fn post_unary(
+    expr: &dc_parser::Expression,
+    op: &AffixesUnaryOperator,
+    a: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let var_no = assignable(a, ns, symbol_table)?;
+    let ty = symbol_table.vars[var_no].ty.clone();
+
+    match (op, ty) {
+        (AffixesUnaryOperator::Increment, ty) if ty.is_integer() => Ok(Expression::PostIncrement {
+            location: expr.location,
+            ty,
+            var_no,
+        }),
+        (AffixesUnaryOperator::Decrement, ty) if ty.is_integer() => Ok(Expression::PostDecrement {
+            location: expr.location,
+            ty,
+            var_no,
+        }),
+        (_, ty) if is_unresolved(&ty) => Err(()),
+        (op, ty) => {
+            let symbol = match op {
+                AffixesUnaryOperator::Increment => "++",
+                AffixesUnaryOperator::Decrement => "--",
+            };
+            ns.diagnostics.push(Diagnostic::type_error(
+                expr.location,
+                format!("operator ‘{}’ cannot be applied to ‘{}’", symbol, ty),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// The variable a value can be assigned to
+pub fn assignable(
+    expr: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<usize, ()> {
+    match expression(expr, ns, symbol_table)? {
+        Expression::Variable { var_no, .. } => Ok(var_no),
+        _ => {
+            ns.diagnostics.push(Diagnostic::error(
+                expr.location,
+                "expression is not assignable".to_string(),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// The variable and the fields of nested structs a value is stored into, like `a.b.c`
+pub fn place(expr: &Expression) -> Option<(usize, Vec<usize>)> {
+    match expr {
+        Expression::Variable { var_no, .. } => Some((*var_no, vec![])),
+        Expression::StructMember { expr, field, .. } => {
+            let (var_no, mut fields) = place(expr)?;
+            fields.push(*field);
+            Some((var_no, fields))
+        }
+        _ => None,
+    }
+}
+
+/// `value.name`, a field of a struct
+fn member_access(
+    expr: &dc_parser::Expression,
+    value: &dc_parser::Expression,
+    name: &Identifier,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let value = expression(value, ns, symbol_table)?;
+
+    match value.ty() {
+        Type::Struct { struct_no, .. } => {
+            let decl = &ns.structs[struct_no];
+            match decl.field(&name.name) {
+                Some(field) => Ok(Expression::StructMember {
+                    location: expr.location,
+                    ty: decl.fields[field].ty.clone(),
+                    expr: Box::new(value),
+                    field,
+                }),
+                None => {
+                    let message = format!("struct ‘{}’ has no field ‘{}’", decl.name, name.name);
+                    ns.diagnostics.push(Diagnostic::error(name.loc, message));
+                    Err(())
+                }
+            }
+        }
+        _ if unresolved(&value) => Err(()),
+        ty => {
+            ns.diagnostics.push(Diagnostic::type_error(
+                name.loc,
+                format!("‘{}’ has no field ‘{}’", ty, name.name),
+            ));
+            Err(())
+        }
     }
 }
 
+/// `Summary { name: value }`, every field of the struct is given a value exactly once
+fn struct_literal(
+    expr: &dc_parser::Expression,
+    name: &Identifier,
+    fields: &[(Identifier, dc_parser::Expression)],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let struct_no = match ns.find_struct(name) {
+        Some(struct_no) => struct_no,
+        None => {
+            ns.diagnostics.push(Diagnostic::decl_error(
+                name.loc,
+                format!("struct ‘{}’ not found", name.name),
+            ));
+            return Err(());
+        }
+    };
+    let decl = ns.structs[struct_no].clone();
+
+    let mut given = vec![false; decl.fields.len()];
+    let mut values = vec![None; decl.fields.len()];
+    let mut broken = false;
+
+    for (field, value) in fields {
+        let value = expression(value, ns, symbol_table);
+
+        let field_no = match decl.field(&field.name) {
+            Some(field_no) => field_no,
+            None => {
+                ns.diagnostics.push(Diagnostic::error(
+                    field.loc,
+                    format!("struct ‘{}’ has no field ‘{}’", decl.name, field.name),
+                ));
+                broken = true;
+                continue;
+            }
+        };
+
+        if given[field_no] {
+            ns.diagnostics.push(Diagnostic::error(
+                field.loc,
+                format!("field ‘{}’ is already initialized", field.name),
+            ));
+            broken = true;
+            continue;
+        }
+        given[field_no] = true;
+
+        match value {
+            Ok(value) => {
+                values[field_no] = Some(implicit_conversion(value, &decl.fields[field_no].ty))
+            }
+            Err(()) => broken = true,
+        }
+    }
+
+    let missing = decl
+        .fields
+        .iter()
+        .zip(&given)
+        .filter(|(_, given)| !**given)
+        .map(|(field, _)| format!("‘{}’", field.name))
+        .collect::<Vec<String>>();
+
+    if !missing.is_empty() {
+        let kind = if missing.len() == 1 {
+            "field"
+        } else {
+            "fields"
+        };
+        ns.diagnostics.push(Diagnostic::error(
+            expr.location,
+            format!(
+                "missing {} {} of struct ‘{}’",
+                kind,
+                missing.join(", "),
+                decl.name
+            ),
+        ));
+        return Err(());
+    }
+
+    if broken {
+        return Err(());
+    }
+
+    Ok(Expression::StructLiteral {
+        location: expr.location,
+        ty: Type::Struct {
+            struct_no,
+            name: decl.name.clone(),
+        },
+        values: values.into_iter().flatten().collect(),
+    })
+}
+
+/// Resolve both operands, so the errors of both are reported
+fn operands(
+    a: &dc_parser::Expression,
+    b: &dc_parser::Expression,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<(Expression, Expression), ()> {
+    let left = expression(a, ns, symbol_table);
+    let right = expression(b, ns, symbol_table);
+
+    Ok((left?, right?))
+}
+
+fn mismatched_operands(
+    location: Loc,
+    symbol: &str,
+    left: &Expression,
+    right: &Expression,
+    ns: &mut Namespace,
+) -> Result<Expression, ()> {
+    if unresolved(left) || unresolved(right) {
+        return Err(());
+    }
+
+    ns.diagnostics.push(Diagnostic::type_error(
+        location,
+        format!(
+            "operator ‘{}’ cannot be applied to ‘{}’ and ‘{}’",
+            symbol,
+            left.ty(),
+            right.ty()
+        ),
+    ));
+    Err(())
+}
+
+/// A value of a type which could not be resolved, like a variable declared with it
+fn unresolved(expr: &Expression) -> bool {
+    is_unresolved(&expr.ty())
+}
+
+fn unsupported(
+    expr: &dc_parser::Expression,
+    kind: &str,
+    ns: &mut Namespace,
+) -> Result<dc_hir::Expression, ()> {
+    ns.diagnostics.push(Diagnostic::error(
+        expr.location,
+        format!("{} expression is not supported yet", kind),
+    ));
+    Err(())
+}
+
 fn function_call_expr(
     function: &Box<dc_parser::Expression>,
     args: &Vec<Argument>,
     ns: &mut Namespace,
     symtable: &mut SymbolTable,
 ) -> Result<Expression, ()> {
-    // todo: match for MemberAccess
-    function_call(function, args, ns, symtable)
+    match &function.node {
+        ExpressionType::MemberAccess { value, name } => {
+            if let ExpressionType::Identifier { id } = &value.node {
+                // a variable shadows a package, struct or object of the same name
+                if symtable.find(&id.name).is_none() {
+                    if let Some(struct_no) = ns.find_struct(id) {
+                        if name.name != "new" {
+                            ns.diagnostics.push(Diagnostic::error(
+                                name.loc,
+                                format!("struct ‘{}’ has no constructor ‘{}’", id.name, name.name),
+                            ));
+                            return Err(());
+                        }
+
+                        return constructor_call(
+                            &function.location,
+                            struct_no,
+                            id,
+                            args,
+                            ns,
+                            symtable,
+                        );
+                    }
+
+                    if let Some(object_no) = ns.find_object(id) {
+                        return object_function_call(
+                            &function.location,
+                            object_no,
+                            name,
+                            args,
+                            ns,
+                            symtable,
+                        );
+                    }
+
+                    if let Some(package_no) = ns.imported_package(id.loc.file_no(), &id.name) {
+                        let package = ns.packages[package_no].name.clone();
+                        return package_function_call(
+                            &function.location,
+                            &package,
+                            name,
+                            args,
+                            ns,
+                            symtable,
+                        );
+                    }
+
+                    ns.diagnostics.push(Diagnostic::decl_error(
+                        id.loc,
+                        format!("‘{}’ is not an imported package", id.name),
+                    ));
+                    return Err(());
+                }
+            }
+
+            let receiver = expression(value, ns, symtable)?;
+            method_call(&function.location, receiver, name, args, ns, symtable)
+        }
+        ExpressionType::Type { .. } => {
+            let to = ns.resolve_type(function)?;
+            explicit_conversion(&function.location, to, args, ns, symtable)
+        }
+        _ => function_call(function, args, ns, symtable),
+    }
+}
+
+/// Call `value.name(args)`, a method of the struct of the value
+fn method_call(
+    location: &Loc,
+    receiver: Expression,
+    name: &Identifier,
+    args: &[Argument],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let struct_no = match receiver.ty() {
+        Type::Struct { struct_no, .. } => struct_no,
+        _ if unresolved(&receiver) => return Err(()),
+        ty => {
+            ns.diagnostics.push(Diagnostic::type_error(
+                name.loc,
+                format!("‘{}’ has no method ‘{}’", ty, name.name),
+            ));
+            return Err(());
+        }
+    };
+
+    let decl = &ns.structs[struct_no];
+    let function_no = decl.functions.iter().copied().find(|function_no| {
+        let function = &ns.functions[*function_no];
+        function.name == name.name && !function.constructor
+    });
+
+    match function_no {
+        Some(function_no) => call(
+            location,
+            function_no,
+            Some(receiver),
+            args,
+            ns,
+            symbol_table,
+        ),
+        None => {
+            let message = format!("struct ‘{}’ has no method ‘{}’", decl.name, name.name);
+            ns.diagnostics.push(Diagnostic::error(name.loc, message));
+            Err(())
+        }
+    }
+}
+
+/// Call `Summary.new(args)` or `Summary(args)`, the constructor of the struct
+fn constructor_call(
+    location: &Loc,
+    struct_no: usize,
+    id: &Identifier,
+    args: &[Argument],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let function_no = ns.structs[struct_no]
+        .functions
+        .iter()
+        .copied()
+        .find(|function_no| ns.functions[*function_no].constructor);
+
+    match function_no {
+        Some(function_no) => call(location, function_no, None, args, ns, symbol_table),
+        None => {
+            ns.diagnostics.push(Diagnostic::error(
+                id.loc,
+                format!("struct ‘{}’ has no constructor ‘new’", id.name),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// Call `Object.name(args)`, a function of the object
+fn object_function_call(
+    location: &Loc,
+    object_no: usize,
+    name: &Identifier,
+    args: &[Argument],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let decl = &ns.objects[object_no];
+    let function_no = decl
+        .functions
+        .iter()
+        .copied()
+        .find(|function_no| ns.functions[*function_no].name == name.name);
+
+    match function_no {
+        Some(function_no) => call(location, function_no, None, args, ns, symbol_table),
+        None => {
+            let message = format!("object ‘{}’ has no function ‘{}’", decl.name, name.name);
+            ns.diagnostics.push(Diagnostic::error(name.loc, message));
+            Err(())
+        }
+    }
+}
+
+/// Call `package.name(args)`, a builtin of the package or one of its functions
+fn package_function_call(
+    location: &Loc,
+    package: &str,
+    name: &Identifier,
+    args: &Vec<Argument>,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    if builtin::is_builtin_call(ns, Some(package), &name.name) {
+        return builtin::resolve_call(location, Some(package), ns, &name.name, args, symbol_table);
+    }
+
+    internal_function_call(location, Some(package), name, args, ns, symbol_table)
 }
 
 fn function_call(
@@ -66,7 +972,7 @@ fn function_call(
 ) -> Result<Expression, ()> {
     match &var.node {
         ExpressionType::Identifier { id } => {
-            let is_builtin = builtin::is_builtin_call(None, &*id.name);
+            let is_builtin = builtin::is_builtin_call(ns, None, &*id.name);
 
             if is_builtin {
                 let result =
@@ -74,17 +980,122 @@ fn function_call(
                 return result;
             }
 
-            let function = expression(var, ns, symbol_table)?;
-            return Ok(Expression::InternalFunctionCall {
-                location: var.location,
-                args: vec![],
-                function: Box::new(function),
-            });
+            if let Some(struct_no) = ns.find_struct(id) {
+                return constructor_call(&var.location, struct_no, id, args, ns, symbol_table);
+            }
+
+            // functions of the own package can be called without qualification
+            let package = ns.files[id.loc.file_no()].package.clone();
+            return internal_function_call(
+                &var.location,
+                package.as_deref(),
+                id,
+                args,
+                ns,
+                symbol_table,
+            );
         }
         _ => {
-            println!("{:?}", &var.node);
+            ns.diagnostics.push(Diagnostic::error(
+                var.location,
+                "expression is not callable".to_string(),
+            ));
         }
     }
 
     return Err(());
 }
+
+/// Call a free function of the package. Within an object, its own functions
+/// are found first.
+fn internal_function_call(
+    location: &Loc,
+    package: Option<&str>,
+    id: &Identifier,
+    args: &Vec<Argument>,
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let object = symbol_table
+        .function_no
+        .and_then(|function_no| ns.functions[function_no].object.clone());
+    let find = |object: &Option<String>| {
+        ns.functions.iter().position(|func| {
+            func.name == id.name
+                && func.package.as_deref() == package
+                && func.receiver.is_none()
+                && func.object == *object
+        })
+    };
+    let function_no = match object {
+        Some(_) => find(&object).or_else(|| find(&None)),
+        None => find(&None),
+    };
+
+    match function_no {
+        Some(function_no) => call(location, function_no, None, args, ns, symbol_table),
+        None => {
+            let name = match package {
+                Some(package) => format!("{}.{}", package, id.name),
+                None => id.name.clone(),
+            };
+            ns.diagnostics.push(Diagnostic::decl_error(
+                id.loc,
+                format!("unknown function ‘{}’", name),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// Arguments are converted to the parameter types where possible,
+/// and checked against the parameters by the type checker. The receiver of a method
+/// is passed first. Every argument is resolved, so the errors of each one are reported.
+fn call(
+    location: &Loc,
+    function_no: usize,
+    receiver: Option<Expression>,
+    args: &[Argument],
+    ns: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Expression, ()> {
+    let returns = ns.functions[function_no]
+        .returns
+        .iter()
+        .map(|ret| ret.ty.clone())
+        .collect();
+
+    let mut resolved_args = receiver.into_iter().collect::<Vec<Expression>>();
+    let first_param = resolved_args.len();
+    let mut success = true;
+    for (arg_no, arg) in args.iter().enumerate() {
+        let arg = match expression(&arg.expr, ns, symbol_table) {
+            Ok(arg) => arg,
+            Err(()) => {
+                success = false;
+                continue;
+            }
+        };
+
+        resolved_args.push(
+            match ns.functions[function_no].params.get(first_param + arg_no) {
+                Some(param) => implicit_conversion(arg, &param.ty),
+                None => arg,
+            },
+        );
+    }
+
+    if !success {
+        return Err(());
+    }
+
+    Ok(Expression::InternalFunctionCall {
+        location: *location,
+        returns,
+        args: resolved_args,
+        function: Box::new(Expression::InternalFunction {
+            location: *location,
+            function_no,
+        }),
+    })
+}
@@ -0,0 +1,54 @@
+use crate::neat::struct_function::{resolve_params, resolve_returns};
+use crate::neat::Namespace;
+use dc_hir::Function;
+use dc_lexer::Diagnostic;
+
+/// Declare `fun name() {}` and return its number, or `None` when the name is already taken.
+/// A top-level `fun` is a free function like `default$name`, the `fun` of an object
+/// is only found through the object.
+pub fn function_decl(
+    func_def: &dc_parser::FuncDecl,
+    object_no: Option<usize>,
+    namespace: &mut Namespace,
+) -> Option<usize> {
+    let name = func_def.name.name.to_owned();
+    let package = namespace.files[func_def.loc.file_no()].package.clone();
+    let object = object_no.map(|object_no| namespace.objects[object_no].name.clone());
+
+    if let Some(previous) = namespace.functions.iter().find(|func| {
+        func.name == name
+            && func.package == package
+            && func.receiver.is_none()
+            && func.object == object
+    }) {
+        let message = match &object {
+            Some(object) => format!(
+                "function ‘{}’ of object ‘{}’ is already defined",
+                name, object
+            ),
+            None => format!("function ‘{}’ is already defined", name),
+        };
+        namespace.diagnostics.push(Diagnostic::error_with_note(
+            func_def.name.loc,
+            message,
+            previous.location,
+            format!("previous definition of ‘{}’", name),
+        ));
+        return None;
+    }
+
+    let params = resolve_params(&func_def.params, namespace);
+    let (returns, _return_success) = resolve_returns(&func_def.returns, namespace);
+
+    let mut function = Function::new(func_def.name.loc, package, None, name, params, returns);
+    function.object = object;
+
+    namespace.functions.push(function);
+    let function_no = namespace.functions.len() - 1;
+
+    if let Some(object_no) = object_no {
+        namespace.objects[object_no].functions.push(function_no);
+    }
+
+    Some(function_no)
+}
@@ -0,0 +1,226 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use dc_lexer::{Diagnostic, Loc};
+use dc_parser::{Import, Program, ProgramUnit};
+
+use crate::neat::program::parse_file;
+use crate::neat::{Namespace, Package};
+
+enum ImportTarget<'a> {
+    /// `import fmt` or `fmt as f`, a package directory in one of the search paths
+    Name(&'a str),
+    /// `import "path" as x;`, relative to the importing file or a search path
+    Path(&'a str),
+}
+
+/// Load the packages imported by a file into the namespace, including the packages these import
+/// in turn. Returns the programs of all newly loaded files.
+pub fn resolve_imports(program: &Program, file_no: usize, ns: &mut Namespace) -> Vec<Program> {
+    let mut loaded = Vec::new();
+    file_imports(program, file_no, &mut Vec::new(), &mut loaded, ns);
+    loaded
+}
+
+fn file_imports(
+    program: &Program,
+    file_no: usize,
+    stack: &mut Vec<usize>,
+    loaded: &mut Vec<Program>,
+    ns: &mut Namespace,
+) {
+    for part in &program.0 {
+        let (loc, alias, target) = match part {
+            ProgramUnit::ImportDecl(Import::Standard(id)) => {
+                (id.loc, &id.name, ImportTarget::Name(&id.name))
+            }
+            ProgramUnit::ImportDecl(Import::Alias(name, id)) => {
+                (name.loc, &id.name, ImportTarget::Name(&name.name))
+            }
+            ProgramUnit::ImportDecl(Import::GlobalSymbol(path, id)) => {
+                (path.loc, &id.name, ImportTarget::Path(&path.string))
+            }
+            ProgramUnit::ImportDecl(Import::Rename(path, _)) => {
+                ns.diagnostics.push(Diagnostic::error(
+                    path.loc,
+                    "this form of import is not supported yet".to_string(),
+                ));
+                continue;
+            }
+            _ => continue,
+        };
+
+        let path = match find_package(&target, file_no, ns) {
+            Some(path) => path,
+            None => {
+                let message = match target {
+                    ImportTarget::Name(name) => format!("package ‘{}’ not found", name),
+                    ImportTarget::Path(path) => format!("package ‘{}’ not found", path),
+                };
+                ns.diagnostics.push(Diagnostic::decl_error(loc, message));
+                continue;
+            }
+        };
+
+        let package_no = match ns.packages.iter().position(|package| package.path == path) {
+            Some(package_no) => {
+                if let Some(pos) = stack.iter().position(|no| *no == package_no) {
+                    let cycle = stack[pos..]
+                        .iter()
+                        .chain(std::iter::once(&package_no))
+                        .map(|no| format!("‘{}’", ns.packages[*no].name))
+                        .collect::<Vec<String>>()
+                        .join(" -> ");
+
+                    ns.diagnostics.push(Diagnostic::decl_error(
+                        loc,
+                        format!("import cycle between packages {}", cycle),
+                    ));
+                }
+                package_no
+            }
+            None => match load_package(path, loc, stack, loaded, ns) {
+                Some(package_no) => package_no,
+                None => continue,
+            },
+        };
+
+        if ns.files[file_no].imports.contains_key(alias) {
+            ns.diagnostics.push(Diagnostic::decl_error(
+                loc,
+                format!("‘{}’ is already imported", alias),
+            ));
+            continue;
+        }
+
+        ns.files[file_no]
+            .imports
+            .insert(alias.to_string(), package_no);
+    }
+}
+
+/// Parse every `.cj` file of the package, the package is named by the `pkg` declaration
+/// of its files, or else by the directory.
+fn load_package(
+    path: PathBuf,
+    loc: Loc,
+    stack: &mut Vec<usize>,
+    loaded: &mut Vec<Program>,
+    ns: &mut Namespace,
+) -> Option<usize> {
+    let filenames = match package_files(&path) {
+        Ok(filenames) => filenames,
+        Err(err) => {
+            ns.diagnostics.push(Diagnostic::decl_error(
+                loc,
+                format!("cannot read package ‘{}’: {}", path.display(), err),
+            ));
+            return None;
+        }
+    };
+
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let package_no = ns.packages.len();
+    ns.packages.push(Package {
+        name,
+        path,
+        files: vec![],
+    });
+
+    let mut programs = Vec::new();
+    let mut declared: Option<String> = None;
+    for filename in filenames {
+        let source = match fs::read_to_string(&filename) {
+            Ok(source) => source,
+            Err(err) => {
+                ns.diagnostics.push(Diagnostic::decl_error(
+                    loc,
+                    format!("cannot read file ‘{}’: {}", filename.display(), err),
+                ));
+                continue;
+            }
+        };
+
+        let file_no = ns.add_file(&filename.to_string_lossy(), &source);
+        let program = parse_file(&source, file_no, ns);
+
+        for part in &program.0 {
+            if let ProgramUnit::PackageDecl(dc_parser::Package::Plain(id)) = part {
+                match &declared {
+                    Some(name) if *name != id.name => {
+                        ns.diagnostics.push(Diagnostic::decl_error(
+                            id.loc,
+                            format!("expected package ‘{}’, found ‘{}’", name, id.name),
+                        ));
+                    }
+                    Some(_) => {}
+                    None => declared = Some(id.name.clone()),
+                }
+            }
+        }
+
+        ns.packages[package_no].files.push(file_no);
+        programs.push((file_no, program));
+    }
+
+    if let Some(name) = declared {
+        ns.packages[package_no].name = name;
+    }
+
+    let name = ns.packages[package_no].name.clone();
+    for (file_no, _) in &programs {
+        ns.files[*file_no].package = Some(name.clone());
+    }
+
+    stack.push(package_no);
+    for (file_no, program) in &programs {
+        file_imports(program, *file_no, stack, loaded, ns);
+    }
+    stack.pop();
+
+    loaded.extend(programs.into_iter().map(|(_, program)| program));
+
+    Some(package_no)
+}
+
+fn package_files(path: &Path) -> std::io::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(vec![path.to_path_buf()]);
+    }
+
+    let mut filenames = fs::read_dir(path)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && path.extension() == Some(OsStr::new("cj")))
+        .collect::<Vec<PathBuf>>();
+    filenames.sort();
+
+    Ok(filenames)
+}
+
+fn find_package(target: &ImportTarget, file_no: usize, ns: &Namespace) -> Option<PathBuf> {
+    let path = match target {
+        ImportTarget::Name(name) => ns
+            .search_paths
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_dir()),
+        ImportTarget::Path(path) => {
+            let importer = Path::new(&ns.source_map.file(file_no).name)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default();
+
+            std::iter::once(importer)
+                .chain(ns.search_paths.iter().cloned())
+                .map(|dir| dir.join(path))
+                .find(|path| path.exists())
+        }
+    };
+
+    path.and_then(|path| path.canonicalize().ok())
+}
@@ -1,16 +1,28 @@
+pub use constructor::*;
 pub use expression::*;
+pub use function::*;
+pub use import::*;
 pub use namespace::*;
+pub use object::*;
 pub use program::*;
 pub use statements::*;
+pub use struct_decl::*;
 pub use struct_function::*;
 pub use symbol_table::*;
+pub use type_check::*;
 pub use unit::*;
 
 pub mod builtin;
+pub mod constructor;
 pub mod expression;
+pub mod function;
+pub mod import;
 pub mod namespace;
+pub mod object;
 pub mod program;
 pub mod statements;
+pub mod struct_decl;
 pub mod struct_function;
 pub mod symbol_table;
+pub mod type_check;
 pub mod unit;
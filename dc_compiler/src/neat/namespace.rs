@@ -1,37 +1,164 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::builtin::HostFunction;
 use crate::ControlFlowGraph;
-use dc_hir::{Function, StructDecl};
+use dc_hir::{Function, ObjectDecl, StructDecl, Type};
+use dc_lexer::{Diagnostic, SourceMap};
 use dc_parser::ExpressionType;
 
 #[derive(Debug)]
 pub struct Namespace {
-    // todo: add diagnostics
-    pub files: Vec<String>,
+    pub source_map: SourceMap,
+    /// Package and imports of each file, indexed by file number
+    pub files: Vec<File>,
+    /// Directories where imported packages are looked up, in order
+    pub search_paths: Vec<PathBuf>,
+    pub packages: Vec<Package>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// Builtins registered by the embedding program, see `add_builtin`
+    pub builtins: Vec<HostFunction>,
     pub structs: Vec<StructDecl>,
+    pub objects: Vec<ObjectDecl>,
     pub functions: Vec<Function>,
     pub cfgs: Vec<ControlFlowGraph>,
 }
 
+/// A declared type which cannot be resolved is reported where it is written, and stands in
+/// as `Type::Unresolved`. Values of such a type, and places declared with it, are not
+/// checked, so the error is not repeated at each use.
+pub fn is_unresolved(ty: &Type) -> bool {
+    *ty == Type::Unresolved
+}
+
+#[derive(Debug, Default)]
+pub struct File {
+    /// The package this file was imported as, `None` for the files of the program itself.
+    /// Their `pkg` declaration is not used: the files given to the compiler are one program,
+    /// which calls its functions across files unqualified, and whose entry point is `main`.
+    pub package: Option<String>,
+    /// Package number of each imported name
+    pub imports: HashMap<String, usize>,
+}
+
+/// A directory, or single file, loaded through an import.
+#[derive(Debug)]
+pub struct Package {
+    pub name: String,
+    pub path: PathBuf,
+    pub files: Vec<usize>,
+}
+
 impl Namespace {
     pub fn new() -> Self {
         Namespace {
+            source_map: SourceMap::new(),
             files: vec![],
+            search_paths: vec![stdlib_path()],
+            packages: vec![],
+            diagnostics: vec![],
+            builtins: vec![],
             structs: vec![],
+            objects: vec![],
             functions: vec![],
             cfgs: vec![],
         }
     }
 
-    pub fn resolve_type(&mut self, id: &dc_parser::Expression) {
-        self.expr_to_type(&id);
+    /// Register a file and return its file number
+    pub fn add_file(&mut self, name: &str, source: &str) -> usize {
+        self.files.push(File::default());
+        self.source_map.add_file(name, source)
+    }
+
+    /// Register a builtin of the embedding program and return its number. A name may be
+    /// registered again with other argument types, a builtin of the language with the
+    /// same name and arguments is found first.
+    pub fn add_builtin(&mut self, builtin: HostFunction) -> usize {
+        self.builtins.push(builtin);
+        self.builtins.len() - 1
     }
 
-    pub fn expr_to_type<'a>(&mut self, expr: &'a dc_parser::Expression) {
-        let expr = expr;
-        match expr.node {
-            ExpressionType::Call { .. } => {}
+    /// The package imported as `name` in the given file
+    pub fn imported_package(&self, file_no: usize, name: &str) -> Option<usize> {
+        self.files[file_no].imports.get(name).copied()
+    }
+
+    pub fn any_errors(&self) -> bool {
+        self.diagnostics.iter().any(|diag| diag.is_error())
+    }
+
+    /// The struct named in a file, declared by the package of that file
+    pub fn find_struct(&self, id: &dc_parser::Identifier) -> Option<usize> {
+        let package = &self.files[id.loc.file_no()].package;
+
+        self.structs
+            .iter()
+            .position(|def| def.name == id.name && def.package == *package)
+    }
+
+    /// The object named in a file, declared by the package of that file
+    pub fn find_object(&self, id: &dc_parser::Identifier) -> Option<usize> {
+        let package = &self.files[id.loc.file_no()].package;
+
+        self.objects
+            .iter()
+            .position(|def| def.name == id.name && def.package == *package)
+    }
+
+    /// Resolve a declared type, like the type of a field, `Type::Unresolved` if it cannot be.
+    /// See `is_unresolved`.
+    pub fn resolve_declared_type(&mut self, expr: &dc_parser::Expression) -> Type {
+        self.resolve_type(expr).unwrap_or(Type::Unresolved)
+    }
+
+    /// Resolve a type written in the sourcecode, like the type of a parameter
+    pub fn resolve_type(&mut self, expr: &dc_parser::Expression) -> Result<Type, ()> {
+        match &expr.node {
+            ExpressionType::Type { ty } => match ty {
+                dc_parser::Type::Bool => Ok(Type::Bool),
+                dc_parser::Type::String => Ok(Type::String),
+                dc_parser::Type::Int(n) => Ok(Type::Int(*n)),
+                dc_parser::Type::Uint(n) => Ok(Type::Uint(*n)),
+                dc_parser::Type::Float(n) => Ok(Type::Float(*n)),
+                dc_parser::Type::Bytes(n) => Ok(Type::Bytes(*n)),
+                dc_parser::Type::Void => Ok(Type::Void),
+                _ => {
+                    self.diagnostics.push(Diagnostic::type_error(
+                        expr.location,
+                        format!("type ‘{}’ is not supported yet", ty),
+                    ));
+                    Err(())
+                }
+            },
+            ExpressionType::Identifier { id } => match self.find_struct(id) {
+                Some(struct_no) => Ok(Type::Struct {
+                    struct_no,
+                    name: id.name.clone(),
+                }),
+                None => {
+                    self.diagnostics.push(Diagnostic::decl_error(
+                        id.loc,
+                        format!("type ‘{}’ not found", id.name),
+                    ));
+                    Err(())
+                }
+            },
             _ => {
-                println!("{:?}", expr.node);
+                self.diagnostics.push(Diagnostic::type_error(
+                    expr.location,
+                    "expression is not a type".to_string(),
+                ));
+                Err(())
             }
         }
     }
 }
+
+/// The standard library shipped with the compiler, `CHARJ_STDLIB` overrides the location.
+pub fn stdlib_path() -> PathBuf {
+    match std::env::var_os("CHARJ_STDLIB") {
+        Some(path) => PathBuf::from(path),
+        None => PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../stdlib"),
+    }
+}
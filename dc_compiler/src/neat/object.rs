@@ -0,0 +1,42 @@
+use crate::neat::Namespace;
+use dc_hir::ObjectDecl;
+use dc_lexer::Diagnostic;
+
+/// Declare `object Name { ... }` and return its number, or `None` when the name is already
+/// taken by an object or struct of the same package. Its functions are declared by the caller.
+pub fn object_decl(def: &dc_parser::ObjectDecl, namespace: &mut Namespace) -> Option<usize> {
+    let name = def.name.name.to_owned();
+    let package = namespace.files[def.loc.file_no()].package.clone();
+
+    let previous = namespace
+        .objects
+        .iter()
+        .find(|decl| decl.name == name && decl.package == package)
+        .map(|decl| decl.location)
+        .or_else(|| {
+            namespace
+                .structs
+                .iter()
+                .find(|decl| decl.name == name && decl.package == package)
+                .map(|decl| decl.location)
+        });
+
+    if let Some(previous) = previous {
+        namespace.diagnostics.push(Diagnostic::error_with_note(
+            def.name.loc,
+            format!("‘{}’ is already defined", name),
+            previous,
+            format!("previous definition of ‘{}’", name),
+        ));
+        return None;
+    }
+
+    namespace.objects.push(ObjectDecl {
+        location: def.name.loc,
+        package,
+        name,
+        functions: vec![],
+    });
+
+    Some(namespace.objects.len() - 1)
+}
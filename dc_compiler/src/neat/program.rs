@@ -1,13 +1,12 @@
-use crate::neat::unit::resolve_program;
 use crate::neat::Namespace;
-use dc_parser::parser::parse_program;
+use dc_parser::parser::parse_partial;
+use dc_parser::Program;
 
-pub fn program(input: &str, _filename: &str, namespace: &mut Namespace) {
-    let parse_ast = parse_program(input);
-    match parse_ast {
-        Ok(unit) => {
-            resolve_program(unit, namespace);
-        }
-        Err(_) => {}
-    }
+/// Parse a file which is already registered in the namespace.
+pub fn parse_file(input: &str, file_no: usize, namespace: &mut Namespace) -> Program {
+    // keep resolving what could be parsed, so later errors are reported too
+    let (unit, diagnostics) = parse_partial(input, file_no);
+    namespace.diagnostics.extend(diagnostics);
+
+    unit
 }
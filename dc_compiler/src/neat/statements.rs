@@ -1,39 +1,270 @@
-use crate::neat::expression::expression;
-use crate::neat::Namespace;
-use crate::symbol_table::SymbolTable;
-use dc_hir::Statement;
-use dc_parser::StructFuncDecl;
+use crate::neat::constructor::{check_initialized, constructed};
+use crate::neat::expression::{arithmetic, common_type, expression, implicit_conversion, place};
+use crate::neat::{is_unresolved, Namespace};
+use crate::symbol_table::{SymbolTable, SymbolTableType};
+use dc_hir::{BinOpKind, Expression, Statement, Type, Variable};
+use dc_lexer::{Diagnostic, Loc};
+use dc_parser::{ExpressionType, Operator};
+use num_bigint::BigInt;
 
+/// Resolve the body of a declared function, `location` is the whole declaration
 pub fn resolve_function_body(
-    func_def: &StructFuncDecl,
+    body: &[dc_parser::Statement],
+    location: Loc,
     namespace: &mut Namespace,
     function_no: usize,
 ) {
     let mut res = Vec::new();
     let mut symbol_table = SymbolTable::new();
+    symbol_table.typ = SymbolTableType::Function;
+    symbol_table.name = namespace.functions[function_no].name.clone();
+    symbol_table.function_no = Some(function_no);
 
-    statement(&func_def.body, &mut res, namespace, &mut symbol_table);
+    // parameters are the first variables of the function
+    let params = namespace.functions[function_no].params.clone();
+    for param in params {
+        let var = Variable {
+            location: param.location,
+            name: param.name,
+            ty: param.ty,
+        };
+        declare(var, namespace, &mut symbol_table);
+    }
+
+    // the constructor creates its value in `self`, and returns it at the end
+    let constructor = namespace.functions[function_no].constructor;
+    if constructor {
+        let var = Variable {
+            location: namespace.functions[function_no].location,
+            name: "self".to_string(),
+            ty: namespace.functions[function_no].returns[0].ty.clone(),
+        };
+        declare(var, namespace, &mut symbol_table);
+    }
+
+    statement(body, &mut res, namespace, &mut symbol_table);
+
+    if constructor {
+        let name_location = namespace.functions[function_no].location;
+        res.push(Statement::Return {
+            location,
+            value: Some(constructed(function_no, name_location, namespace)),
+        });
+    }
 
     namespace.functions[function_no].body = res;
+    namespace.functions[function_no].vars = symbol_table.vars;
+
+    if constructor {
+        check_initialized(function_no, namespace);
+    }
+}
+
+/// Declare a variable in the current scope, reporting a redeclaration in the same scope
+fn declare(var: Variable, namespace: &mut Namespace, symbol_table: &mut SymbolTable) -> usize {
+    let location = var.location;
+    let name = var.name.clone();
+
+    match symbol_table.add(var) {
+        Ok(var_no) => var_no,
+        Err(previous) => {
+            namespace.diagnostics.push(Diagnostic::error_with_note(
+                location,
+                format!("‘{}’ is already declared", name),
+                symbol_table.vars[previous].location,
+                format!("previous declaration of ‘{}’", name),
+            ));
+            previous
+        }
+    }
+}
+
+/// `let name: ty = value;`, the value is resolved before the name is declared,
+/// so it can refer to a variable of an outer scope with the same name.
+fn variable_decl(
+    stmt: &dc_parser::Statement,
+    target: &dc_parser::Identifier,
+    ty: &dc_parser::Expression,
+    value: &dc_parser::Expression,
+    res: &mut Vec<Statement>,
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) {
+    let declared = namespace.resolve_declared_type(ty);
+    // the constructor of a type which is not found is not reported again
+    let value = if is_unresolved(&declared) && calls_constructor(value, ty) {
+        Err(())
+    } else {
+        expression(value, namespace, symbol_table)
+    };
+
+    let var = Variable {
+        location: target.loc,
+        name: target.name.clone(),
+        ty: declared.clone(),
+    };
+    let var_no = declare(var, namespace, symbol_table);
+
+    if let Ok(value) = value {
+        res.push(Statement::VariableDecl {
+            location: stmt.location,
+            var_no,
+            value: implicit_conversion(value, &declared),
+        });
+    }
+}
+
+/// Whether the value calls a constructor of the type, like `Missing(1)` or `Missing.new(1)`
+fn calls_constructor(value: &dc_parser::Expression, ty: &dc_parser::Expression) -> bool {
+    let callee = match &value.node {
+        ExpressionType::Call { function, .. } => match &function.node {
+            ExpressionType::MemberAccess { value, .. } => value,
+            _ => function,
+        },
+        _ => return false,
+    };
+
+    match (&callee.node, &ty.node) {
+        (ExpressionType::Identifier { id }, ExpressionType::Identifier { id: ty }) => {
+            id.name == ty.name
+        }
+        _ => false,
+    }
 }
 
 pub fn statement(
-    body: &Vec<dc_parser::Statement>,
+    body: &[dc_parser::Statement],
     res: &mut Vec<Statement>,
     namespace: &mut Namespace,
     symbol_table: &mut SymbolTable,
 ) {
     for stmt in body {
         match &stmt.node {
-            dc_parser::StatementType::Break => {}
-            dc_parser::StatementType::Continue => {}
-            dc_parser::StatementType::If { .. } => {}
-            dc_parser::StatementType::While { .. } => {}
-            dc_parser::StatementType::For { .. } => {}
-            dc_parser::StatementType::Loop => {}
-            dc_parser::StatementType::Assign { .. } => {}
-            dc_parser::StatementType::VariableDecl { .. } => {}
-            dc_parser::StatementType::Return { .. } => {}
+            dc_parser::StatementType::Break { label } => {
+                if loop_control(stmt, "break", label, namespace, symbol_table) {
+                    res.push(Statement::Break {
+                        location: stmt.location,
+                        label: label_name(label),
+                    });
+                }
+            }
+            dc_parser::StatementType::Continue { label } => {
+                if loop_control(stmt, "continue", label, namespace, symbol_table) {
+                    res.push(Statement::Continue {
+                        location: stmt.location,
+                        label: label_name(label),
+                    });
+                }
+            }
+            dc_parser::StatementType::If { cond, body, orelse } => {
+                let cond = expression(cond, namespace, symbol_table);
+                let then = block(body, namespace, symbol_table);
+                let otherwise = match orelse {
+                    Some(orelse) => block(orelse, namespace, symbol_table),
+                    None => vec![],
+                };
+
+                if let Ok(cond) = cond {
+                    res.push(Statement::If {
+                        location: stmt.location,
+                        cond,
+                        then,
+                        otherwise,
+                    });
+                }
+            }
+            dc_parser::StatementType::While { label, cond, body } => {
+                let cond = expression(cond, namespace, symbol_table);
+                let body = loop_body(label, body, namespace, symbol_table);
+
+                if let Ok(cond) = cond {
+                    res.push(Statement::While {
+                        location: stmt.location,
+                        label: label_name(label),
+                        cond,
+                        body,
+                    });
+                }
+            }
+            dc_parser::StatementType::For {
+                label,
+                target,
+                iter,
+                body,
+            } => {
+                if let Some(stmt) = for_in(stmt, label, target, iter, body, namespace, symbol_table)
+                {
+                    res.push(stmt);
+                }
+            }
+            dc_parser::StatementType::CFor {
+                label,
+                init,
+                cond,
+                next,
+                body,
+            } => {
+                // the variables of `init` are only visible in the loop
+                symbol_table.enter_scope();
+
+                let mut init_res = Vec::new();
+                if let Some(init) = init {
+                    let init = std::slice::from_ref(init.as_ref());
+                    statement(init, &mut init_res, namespace, symbol_table);
+                }
+
+                let cond = cond
+                    .as_ref()
+                    .map(|cond| expression(cond, namespace, symbol_table))
+                    .transpose();
+
+                let mut next_res = Vec::new();
+                if let Some(next) = next {
+                    let next = std::slice::from_ref(next.as_ref());
+                    statement(next, &mut next_res, namespace, symbol_table);
+                }
+
+                let body = loop_body(label, body, namespace, symbol_table);
+
+                symbol_table.leave_scope();
+
+                if let Ok(cond) = cond {
+                    res.push(Statement::For {
+                        location: stmt.location,
+                        label: label_name(label),
+                        init: init_res,
+                        cond,
+                        next: next_res,
+                        body,
+                    });
+                }
+            }
+            dc_parser::StatementType::Loop { label, body } => {
+                let body = loop_body(label, body, namespace, symbol_table);
+
+                res.push(Statement::Loop {
+                    location: stmt.location,
+                    label: label_name(label),
+                    body,
+                });
+            }
+            dc_parser::StatementType::Assign { target, ty, value } => {
+                variable_decl(stmt, target, ty, value, res, namespace, symbol_table)
+            }
+            dc_parser::StatementType::VariableDecl { .. } => {
+                unsupported(stmt, "variable declaration", namespace)
+            }
+            dc_parser::StatementType::Assignment { target, op, value } => {
+                assignment(stmt, target, op, value, res, namespace, symbol_table)
+            }
+            dc_parser::StatementType::Return { value } => {
+                if let Ok(value) = return_value(&stmt.location, value, namespace, symbol_table) {
+                    res.push(Statement::Return {
+                        location: stmt.location,
+                        value,
+                    });
+                }
+            }
             dc_parser::StatementType::Expression { expr } => {
                 let result = expression(&expr, namespace, symbol_table);
                 match result {
@@ -43,9 +274,538 @@ pub fn statement(
                             expression,
                         });
                     }
+                    // the diagnostic is already in the namespace
                     Err(_) => {}
                 }
             }
         }
     }
 }
+
+/// `target = value` or `target op= value`, a compound assignment reads the target first.
+/// The target is a variable, or a field of a struct variable.
+fn assignment(
+    stmt: &dc_parser::Statement,
+    target: &dc_parser::Expression,
+    op: &Option<Operator>,
+    value: &dc_parser::Expression,
+    res: &mut Vec<Statement>,
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) {
+    let resolved = expression(target, namespace, symbol_table);
+    let value = expression(value, namespace, symbol_table);
+
+    let (current, value) = match (resolved, value) {
+        (Ok(current), Ok(value)) => (current, value),
+        _ => return,
+    };
+
+    let (var_no, fields) = match place(&current) {
+        Some(place) => place,
+        None => {
+            namespace.diagnostics.push(Diagnostic::error(
+                target.location,
+                "expression is not assignable".to_string(),
+            ));
+            return;
+        }
+    };
+
+    let ty = current.ty();
+    let value = match op {
+        Some(op) => match arithmetic(stmt.location, op, current, value, namespace) {
+            Ok(value) => value,
+            Err(()) => return,
+        },
+        None => value,
+    };
+    let value = implicit_conversion(value, &ty);
+
+    if fields.is_empty() {
+        res.push(Statement::Assign {
+            location: stmt.location,
+            var_no,
+            value,
+        });
+    } else {
+        res.push(Statement::AssignField {
+            location: stmt.location,
+            var_no,
+            fields,
+            value,
+        });
+    }
+}
+
+/// `for (x in start..end)` counts from `start` up to before `end`, `for (x in [a, b])`
+/// counts an index over the values and assigns the value at the index before the body.
+/// Both are lowered as a `for` loop. The variable is only visible in the loop.
+fn for_in(
+    stmt: &dc_parser::Statement,
+    label: &Option<dc_parser::Identifier>,
+    target: &dc_parser::Expression,
+    iter: &dc_parser::Expression,
+    body: &[dc_parser::Statement],
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Option<Statement> {
+    let name = match &target.node {
+        ExpressionType::Identifier { id } => id,
+        _ => {
+            namespace.diagnostics.push(Diagnostic::error(
+                target.location,
+                "the variable of a for loop must be a name".to_string(),
+            ));
+            return None;
+        }
+    };
+
+    symbol_table.enter_scope();
+
+    let iteration = match &iter.node {
+        ExpressionType::Range { start, end } => {
+            let start = expression(start, namespace, symbol_table);
+            let end = expression(end, namespace, symbol_table);
+            match (start, end) {
+                (Ok(start), Ok(end)) => range(iter, start, end, namespace),
+                _ => Err(()),
+            }
+        }
+        ExpressionType::List { elements } => {
+            let values = elements
+                .iter()
+                .map(|element| expression(element, namespace, symbol_table))
+                .collect::<Vec<Result<Expression, ()>>>();
+            match values.into_iter().collect() {
+                Ok(values) => list(iter, values, namespace),
+                Err(()) => Err(()),
+            }
+        }
+        _ => {
+            namespace.diagnostics.push(Diagnostic::error(
+                iter.location,
+                "only ranges and lists can be iterated".to_string(),
+            ));
+            Err(())
+        }
+    };
+
+    let ty = match &iteration {
+        Ok(Iteration::Range { ty, .. }) | Ok(Iteration::List { ty, .. }) => ty.clone(),
+        Err(()) => Type::Unresolved,
+    };
+    let var = Variable {
+        location: name.loc,
+        name: name.name.clone(),
+        ty: ty.clone(),
+    };
+    let var_no = declare(var, namespace, symbol_table);
+
+    // the end of a range, or the values of a list, are evaluated once, into variables that
+    // cannot be named. A list is walked by an index.
+    let mut hidden = Vec::new();
+    match &iteration {
+        Ok(Iteration::Range { end, .. }) => {
+            let location = end.location().unwrap_or(iter.location);
+            hidden.push(hidden_variable(
+                "for.end",
+                location,
+                &ty,
+                namespace,
+                symbol_table,
+            ));
+        }
+        Ok(Iteration::List { values, .. }) => {
+            for (value_no, value) in values.iter().enumerate() {
+                let location = value.location().unwrap_or(iter.location);
+                let name = format!("for.value.{}", value_no);
+                hidden.push(hidden_variable(
+                    &name,
+                    location,
+                    &ty,
+                    namespace,
+                    symbol_table,
+                ));
+            }
+            hidden.push(hidden_variable(
+                "for.index",
+                iter.location,
+                &INDEX_TYPE,
+                namespace,
+                symbol_table,
+            ));
+        }
+        Err(()) => {}
+    }
+
+    let body = loop_body(label, body, namespace, symbol_table);
+
+    symbol_table.leave_scope();
+
+    let variable = |location, ty: &Type, var_no| Expression::Variable {
+        location,
+        ty: ty.clone(),
+        var_no,
+    };
+
+    // the variable counted up to the end, the variable of a range or the index of a list
+    let (init, counter_ty, counter_no, end, body) = match iteration {
+        Ok(Iteration::Range { start, end, .. }) => {
+            let end_no = hidden[0];
+            let init = vec![
+                Statement::VariableDecl {
+                    location: iter.location,
+                    var_no,
+                    value: start,
+                },
+                Statement::VariableDecl {
+                    location: iter.location,
+                    var_no: end_no,
+                    value: end,
+                },
+            ];
+            let end = variable(iter.location, &ty, end_no);
+            (init, ty, var_no, end, body)
+        }
+        Ok(Iteration::List { values, .. }) => {
+            let (index_no, value_nos) = hidden.split_last().unwrap();
+            let mut init: Vec<Statement> = values
+                .into_iter()
+                .zip(value_nos)
+                .map(|(value, value_no)| Statement::VariableDecl {
+                    location: value.location().unwrap_or(iter.location),
+                    var_no: *value_no,
+                    value,
+                })
+                .collect();
+            init.push(Statement::VariableDecl {
+                location: iter.location,
+                var_no: *index_no,
+                value: index_literal(iter.location, 0),
+            });
+
+            let index = variable(iter.location, &INDEX_TYPE, *index_no);
+            let mut select = vec![select_value(iter.location, var_no, &ty, &index, value_nos)];
+            select.extend(body);
+
+            let end = index_literal(iter.location, value_nos.len());
+            (init, INDEX_TYPE, *index_no, end, select)
+        }
+        Err(()) => return None,
+    };
+
+    let next = Expression::PostIncrement {
+        location: iter.location,
+        ty: counter_ty.clone(),
+        var_no: counter_no,
+    };
+
+    Some(Statement::For {
+        location: stmt.location,
+        label: label_name(label),
+        init,
+        cond: Some(Expression::Binary {
+            location: iter.location,
+            ty: Type::Bool,
+            op: BinOpKind::Lt,
+            left: Box::new(variable(iter.location, &counter_ty, counter_no)),
+            right: Box::new(end),
+        }),
+        next: vec![Statement::Expression {
+            location: iter.location,
+            expression: next,
+        }],
+        body,
+    })
+}
+
+/// The type of the index which walks a list
+const INDEX_TYPE: Type = Type::Uint(32);
+
+fn index_literal(location: Loc, index: usize) -> Expression {
+    Expression::NumberLiteral {
+        location,
+        ty: INDEX_TYPE,
+        value: BigInt::from(index),
+    }
+}
+
+fn hidden_variable(
+    name: &str,
+    location: Loc,
+    ty: &Type,
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> usize {
+    let var = Variable {
+        location,
+        name: name.to_string(),
+        ty: ty.clone(),
+    };
+    declare(var, namespace, symbol_table)
+}
+
+/// Assign the value of a list at the index to the variable of the loop, each value is
+/// picked by comparing the index with its position
+fn select_value(
+    location: Loc,
+    var_no: usize,
+    ty: &Type,
+    index: &Expression,
+    value_nos: &[usize],
+) -> Statement {
+    let assign = |value_no| Statement::Assign {
+        location,
+        var_no,
+        value: Expression::Variable {
+            location,
+            ty: ty.clone(),
+            var_no: value_no,
+        },
+    };
+
+    // the last value is the one left when the index is none of the others
+    let (last, others) = value_nos.split_last().unwrap();
+    let mut select = assign(*last);
+    for (position, value_no) in others.iter().enumerate().rev() {
+        select = Statement::If {
+            location,
+            cond: Expression::Binary {
+                location,
+                ty: Type::Bool,
+                op: BinOpKind::Eq,
+                left: Box::new(index.clone()),
+                right: Box::new(index_literal(location, position)),
+            },
+            then: vec![assign(*value_no)],
+            otherwise: vec![select],
+        };
+    }
+
+    select
+}
+
+enum Iteration {
+    Range {
+        ty: Type,
+        start: Expression,
+        end: Expression,
+    },
+    List {
+        ty: Type,
+        values: Vec<Expression>,
+    },
+}
+
+/// The bounds of a range are integers, converted to the wider of both types
+fn range(
+    iter: &dc_parser::Expression,
+    start: Expression,
+    end: Expression,
+    namespace: &mut Namespace,
+) -> Result<Iteration, ()> {
+    match common_type(&start, &end) {
+        Some(ty) if ty.is_integer() => Ok(Iteration::Range {
+            start: implicit_conversion(start, &ty),
+            end: implicit_conversion(end, &ty),
+            ty,
+        }),
+        _ => {
+            namespace.diagnostics.push(Diagnostic::type_error(
+                iter.location,
+                format!(
+                    "range bounds must be integers, found ‘{}’ and ‘{}’",
+                    start.ty(),
+                    end.ty()
+                ),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// The values of a list have the same type, integers are converted to the widest one
+fn list(
+    iter: &dc_parser::Expression,
+    values: Vec<Expression>,
+    namespace: &mut Namespace,
+) -> Result<Iteration, ()> {
+    // the value of the type so far, a number literal may still take the type of a later one
+    let mut widest = match values.first() {
+        Some(value) => value,
+        None => {
+            namespace.diagnostics.push(Diagnostic::error(
+                iter.location,
+                "an empty list cannot be iterated".to_string(),
+            ));
+            return Err(());
+        }
+    };
+
+    for value in &values[1..] {
+        let (l, r) = (widest.ty(), value.ty());
+
+        match common_type(widest, value) {
+            Some(ty) if ty == r => widest = value,
+            Some(_) => {}
+            None if l == r => {}
+            None => {
+                namespace.diagnostics.push(Diagnostic::type_error(
+                    value.location().unwrap_or(iter.location),
+                    format!(
+                        "list elements must have the same type, found ‘{}’ and ‘{}’",
+                        l, r
+                    ),
+                ));
+                return Err(());
+            }
+        }
+    }
+
+    let ty = widest.ty();
+    let values = values
+        .into_iter()
+        .map(|value| implicit_conversion(value, &ty))
+        .collect();
+
+    Ok(Iteration::List { ty, values })
+}
+
+/// The statements of a nested block, with their own scope for variables
+fn block(
+    body: &[dc_parser::Statement],
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Vec<Statement> {
+    let mut res = Vec::new();
+
+    symbol_table.enter_scope();
+    statement(body, &mut res, namespace, symbol_table);
+    symbol_table.leave_scope();
+
+    res
+}
+
+/// The body of a loop, a label must differ from the labels of the loops around it
+fn loop_body(
+    label: &Option<dc_parser::Identifier>,
+    body: &[dc_parser::Statement],
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Vec<Statement> {
+    if let Some(label) = label {
+        if symbol_table.loops.contains(&Some(label.name.clone())) {
+            namespace.diagnostics.push(Diagnostic::error(
+                label.loc,
+                format!(
+                    "label ‘'{}’ is already used by an enclosing loop",
+                    label.name
+                ),
+            ));
+        }
+    }
+
+    symbol_table.loops.push(label_name(label));
+    let body = block(body, namespace, symbol_table);
+    symbol_table.loops.pop();
+
+    body
+}
+
+fn label_name(label: &Option<dc_parser::Identifier>) -> Option<String> {
+    label.as_ref().map(|label| label.name.clone())
+}
+
+/// `break` and `continue` are only allowed inside a loop, a label names one of the loops
+/// around them
+fn loop_control(
+    stmt: &dc_parser::Statement,
+    keyword: &str,
+    label: &Option<dc_parser::Identifier>,
+    namespace: &mut Namespace,
+    symbol_table: &SymbolTable,
+) -> bool {
+    if symbol_table.loops.is_empty() {
+        namespace.diagnostics.push(Diagnostic::error(
+            stmt.location,
+            format!("‘{}’ outside of a loop", keyword),
+        ));
+        return false;
+    }
+
+    if let Some(label) = label {
+        if !symbol_table.loops.contains(&Some(label.name.clone())) {
+            namespace.diagnostics.push(Diagnostic::error(
+                label.loc,
+                format!("use of undeclared label ‘'{}’", label.name),
+            ));
+            return false;
+        }
+    }
+
+    true
+}
+
+/// The parser wraps returned values in a list, a single value is unwrapped
+/// and converted to the return type. A constructor returns the value it creates.
+fn return_value(
+    location: &Loc,
+    value: &Option<dc_parser::Expression>,
+    namespace: &mut Namespace,
+    symbol_table: &mut SymbolTable,
+) -> Result<Option<dc_hir::Expression>, ()> {
+    if let Some(function_no) = symbol_table.function_no {
+        let function = &namespace.functions[function_no];
+        if function.constructor {
+            return match value {
+                Some(value) => {
+                    let message = format!(
+                        "constructor of ‘{}’ cannot return a value",
+                        function.returns[0].ty
+                    );
+                    namespace
+                        .diagnostics
+                        .push(Diagnostic::error(value.location, message));
+                    Err(())
+                }
+                None => Ok(Some(constructed(function_no, *location, namespace))),
+            };
+        }
+    }
+
+    let value = match value {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+
+    let returns = symbol_table
+        .function_no
+        .and_then(|function_no| namespace.functions[function_no].returns.first())
+        .map(|ret| ret.ty.clone());
+    let convert = |value: dc_hir::Expression| match &returns {
+        Some(ty) => implicit_conversion(value, ty),
+        None => value,
+    };
+
+    match &value.node {
+        dc_parser::ExpressionType::List { elements } if elements.len() == 1 => {
+            expression(&elements[0], namespace, symbol_table).map(|value| Some(convert(value)))
+        }
+        dc_parser::ExpressionType::List { .. } => {
+            namespace.diagnostics.push(Diagnostic::error(
+                value.location,
+                "returning multiple values is not supported yet".to_string(),
+            ));
+            Err(())
+        }
+        _ => expression(value, namespace, symbol_table).map(|value| Some(convert(value))),
+    }
+}
+
+fn unsupported(stmt: &dc_parser::Statement, kind: &str, namespace: &mut Namespace) {
+    namespace.diagnostics.push(Diagnostic::error(
+        stmt.location,
+        format!("{} statement is not supported yet", kind),
+    ));
+}
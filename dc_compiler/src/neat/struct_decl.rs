@@ -0,0 +1,106 @@
+use crate::neat::Namespace;
+use dc_hir::{StructDecl, StructField, Type};
+use dc_lexer::Diagnostic;
+use dc_parser::StatementType;
+
+/// Declare the structs before resolving their fields, so a field can have the type of a struct
+/// declared later, or in another file of the package.
+pub fn resolve_structs(defs: &[&dc_parser::StructDecl], namespace: &mut Namespace) {
+    let mut declared = Vec::new();
+
+    for def in defs {
+        let name = def.name.name.to_owned();
+        let package = namespace.files[def.loc.file_no()].package.clone();
+
+        if let Some(previous) = namespace
+            .structs
+            .iter()
+            .find(|decl| decl.name == name && decl.package == package)
+        {
+            namespace.diagnostics.push(Diagnostic::error_with_note(
+                def.name.loc,
+                format!("struct ‘{}’ is already defined", name),
+                previous.location,
+                format!("previous definition of ‘{}’", name),
+            ));
+            continue;
+        }
+
+        namespace.structs.push(StructDecl {
+            location: def.name.loc,
+            package,
+            name,
+            fields: vec![],
+            functions: vec![],
+        });
+        declared.push((namespace.structs.len() - 1, *def));
+    }
+
+    for (struct_no, def) in &declared {
+        let fields = struct_fields(def, namespace);
+        namespace.structs[*struct_no].fields = fields;
+    }
+
+    for (struct_no, _) in declared {
+        check_recursion(struct_no, namespace);
+    }
+}
+
+fn struct_fields(def: &dc_parser::StructDecl, namespace: &mut Namespace) -> Vec<StructField> {
+    let mut fields: Vec<StructField> = Vec::new();
+
+    for stmt in &def.fields {
+        let (field, ty) = match &stmt.node {
+            StatementType::VariableDecl { field, ty } => (field, ty),
+            _ => continue,
+        };
+
+        let ty = namespace.resolve_declared_type(ty);
+
+        if let Some(previous) = fields.iter().find(|prev| prev.name == field.name) {
+            namespace.diagnostics.push(Diagnostic::error_with_note(
+                field.loc,
+                format!("field ‘{}’ is already declared", field.name),
+                previous.location,
+                format!("previous declaration of ‘{}’", field.name),
+            ));
+            continue;
+        }
+
+        fields.push(StructField {
+            location: field.loc,
+            name: field.name.clone(),
+            ty,
+        });
+    }
+
+    fields
+}
+
+/// A struct cannot contain itself, directly or through the fields of another struct,
+/// as it would never end.
+fn check_recursion(struct_no: usize, namespace: &mut Namespace) {
+    let mut stack = vec![struct_no];
+    let mut seen = vec![struct_no];
+
+    while let Some(current) = stack.pop() {
+        for field in &namespace.structs[current].fields {
+            if let Type::Struct { struct_no: no, .. } = field.ty {
+                if no == struct_no {
+                    let decl = &namespace.structs[struct_no];
+                    let message = format!("struct ‘{}’ contains itself", decl.name);
+                    let location = decl.location;
+                    namespace
+                        .diagnostics
+                        .push(Diagnostic::decl_error(location, message));
+                    return;
+                }
+
+                if !seen.contains(&no) {
+                    seen.push(no);
+                    stack.push(no);
+                }
+            }
+        }
+    }
+}
@@ -1,30 +1,139 @@
 use crate::neat::Namespace;
-use dc_hir::{Function, Parameter};
+use dc_hir::{Function, Parameter, Type};
+use dc_lexer::Diagnostic;
 
+/// Declare the function and return its number, or `None` when the name is already taken
+/// by a function of the same package, in this or any other file. `Summary$name` declares
+/// a method of the struct, `Summary$new` its constructor, `default$name` a free function.
 pub fn struct_function_decl(
     struct_func_def: &dc_parser::StructFuncDecl,
     namespace: &mut Namespace,
-) -> bool {
-    let success = true;
+) -> Option<usize> {
+    let name = struct_func_def.name.name.to_owned();
+    let package = namespace.files[struct_func_def.loc.file_no()]
+        .package
+        .clone();
+    let receiver = resolve_receiver(&struct_func_def.struct_name, namespace).ok()?;
 
-    let params = resolve_params(&struct_func_def.params, namespace);
-    let (returns, _return_success) = resolve_returns(&struct_func_def.returns, namespace);
+    if let Some(previous) = namespace.functions.iter().find(|func| {
+        func.name == name
+            && func.package == package
+            && func.receiver == receiver
+            && func.object.is_none()
+    }) {
+        let message = match &receiver {
+            Some(receiver) => format!("method ‘{}’ of ‘{}’ is already defined", name, receiver),
+            None => format!("function ‘{}’ is already defined", name),
+        };
+        namespace.diagnostics.push(Diagnostic::error_with_note(
+            struct_func_def.name.loc,
+            message,
+            previous.location,
+            format!("previous definition of ‘{}’", name),
+        ));
+        return None;
+    }
 
-    let name = struct_func_def.name.name.to_owned();
+    let mut params = resolve_params(&struct_func_def.params, namespace);
+    let (mut returns, _return_success) = resolve_returns(&struct_func_def.returns, namespace);
+    let constructor = receiver.is_some() && name == "new";
 
-    let function = Function::new(name, params, returns);
+    match &receiver {
+        Some(receiver) if constructor => {
+            if let Some(ty) = &struct_func_def.returns {
+                namespace.diagnostics.push(Diagnostic::decl_error(
+                    ty.location,
+                    format!(
+                        "constructor ‘{}’ of ‘{}’ cannot declare a return type",
+                        name, receiver
+                    ),
+                ));
+            }
+
+            // the constructor returns the value it creates
+            returns = vec![Parameter {
+                location: struct_func_def.name.loc,
+                name: "".to_string(),
+                ty: receiver.clone(),
+            }];
+        }
+        Some(receiver) => {
+            params.insert(
+                0,
+                Parameter {
+                    location: struct_func_def.struct_name.loc,
+                    name: "self".to_string(),
+                    ty: receiver.clone(),
+                },
+            );
+        }
+        None => {}
+    }
+
+    let mut function = Function::new(
+        struct_func_def.name.loc,
+        package,
+        receiver.clone(),
+        name,
+        params,
+        returns,
+    );
+    function.constructor = constructor;
 
     namespace.functions.push(function);
+    let function_no = namespace.functions.len() - 1;
+
+    if let Some(Type::Struct { struct_no, .. }) = receiver {
+        namespace.structs[struct_no].functions.push(function_no);
+    }
 
-    success
+    Some(function_no)
 }
 
+/// The struct a method belongs to, `None` for the free functions of `default`
+fn resolve_receiver(
+    struct_name: &dc_parser::Identifier,
+    namespace: &mut Namespace,
+) -> Result<Option<Type>, ()> {
+    if struct_name.name == "default" {
+        return Ok(None);
+    }
+
+    match namespace.find_struct(struct_name) {
+        Some(struct_no) => Ok(Some(Type::Struct {
+            struct_no,
+            name: struct_name.name.clone(),
+        })),
+        None => {
+            namespace.diagnostics.push(Diagnostic::decl_error(
+                struct_name.loc,
+                format!("struct ‘{}’ not found", struct_name.name),
+            ));
+            Err(())
+        }
+    }
+}
+
+/// The declared return type, `Type::Unresolved` stands in for a type which could not be resolved.
 pub fn resolve_returns(
-    _returns: &Option<dc_parser::Expression>,
-    _namespace: &mut Namespace,
+    returns: &Option<dc_parser::Expression>,
+    namespace: &mut Namespace,
 ) -> (Vec<Parameter>, bool) {
-    let resolved_returns = Vec::new();
-    let success = true;
+    let mut resolved_returns = Vec::new();
+    let mut success = true;
+
+    if let Some(ty) = returns {
+        let resolved = namespace.resolve_type(ty).unwrap_or_else(|_| {
+            success = false;
+            Type::Unresolved
+        });
+
+        resolved_returns.push(Parameter {
+            location: ty.location,
+            name: "".to_string(),
+            ty: resolved,
+        });
+    }
 
     (resolved_returns, success)
 }
@@ -42,11 +151,23 @@ pub fn resolve_params(
             }
         };
 
-        namespace.resolve_type(&p.ty);
+        let name = match &p.name {
+            Some(name) => name.name.clone(),
+            None => {
+                namespace.diagnostics.push(Diagnostic::decl_error(
+                    *loc,
+                    "missing parameter name".to_string(),
+                ));
+                continue;
+            }
+        };
+
+        let ty = namespace.resolve_declared_type(&p.ty);
 
         params.push(Parameter {
             location: *loc,
-            name: p.get_name(),
+            name,
+            ty,
         })
     }
     return params;
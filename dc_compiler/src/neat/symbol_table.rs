@@ -2,6 +2,8 @@ use core::fmt;
 
 use indexmap::map::IndexMap;
 
+use dc_hir::Variable;
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum SymbolTableType {
     Module,
@@ -44,6 +46,19 @@ pub struct SymbolTable {
     /// A list of subscopes in the order as found in the
     /// AST nodes.
     pub sub_tables: Vec<SymbolTable>,
+
+    /// Parameters and local variables, the index is the variable number.
+    pub vars: Vec<Variable>,
+
+    /// Variable numbers by name for each nested block, the innermost block is last.
+    pub scopes: Vec<IndexMap<String, usize>>,
+
+    /// The labels of the loops around the current statement, the innermost loop is last.
+    /// For `break` and `continue`.
+    pub loops: Vec<Option<String>>,
+
+    /// The function whose body is resolved, for its return type.
+    pub function_no: Option<usize>,
 }
 
 impl SymbolTable {
@@ -55,7 +70,42 @@ impl SymbolTable {
             is_nested: false,
             symbols: Default::default(),
             sub_tables: vec![],
+            vars: vec![],
+            scopes: vec![IndexMap::new()],
+            loops: vec![],
+            function_no: None,
+        }
+    }
+
+    /// Declare a variable in the innermost scope and return its number. A variable with
+    /// the same name in the same scope is an error, its number is returned instead.
+    pub fn add(&mut self, var: Variable) -> Result<usize, usize> {
+        let scope = self.scopes.last_mut().unwrap();
+        if let Some(var_no) = scope.get(&var.name) {
+            return Err(*var_no);
         }
+
+        let var_no = self.vars.len();
+        scope.insert(var.name.clone(), var_no);
+        self.vars.push(var);
+
+        Ok(var_no)
+    }
+
+    /// Find a variable by name, inner scopes shadow outer ones.
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    pub fn enter_scope(&mut self) {
+        self.scopes.push(IndexMap::new());
+    }
+
+    pub fn leave_scope(&mut self) {
+        self.scopes.pop();
     }
 }
 
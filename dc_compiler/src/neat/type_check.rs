@@ -0,0 +1,312 @@
+use dc_hir::{Expression, Function, Statement, Type};
+use dc_lexer::{Diagnostic, Loc};
+
+use crate::neat::{is_unresolved, Namespace};
+
+/// Check the resolved function bodies: call arguments against the parameters of the callee,
+/// returned values against the declared return type, and conditions are `bool`.
+pub fn type_check(ns: &mut Namespace) {
+    for function_no in 0..ns.functions.len() {
+        let function = ns.functions[function_no].clone();
+
+        for stmt in &function.body {
+            statement(stmt, &function, ns);
+        }
+    }
+}
+
+fn statement(stmt: &Statement, function: &Function, ns: &mut Namespace) {
+    match stmt {
+        Statement::VariableDecl { var_no, value, .. } | Statement::Assign { var_no, value, .. } => {
+            check_expression(value, ns);
+
+            let var = &function.vars[*var_no];
+            let found = value.ty();
+            if !converts(&found, &var.ty) {
+                ns.diagnostics.push(Diagnostic::type_error(
+                    value.location().unwrap_or(var.location),
+                    mismatch(
+                        value,
+                        &var.ty,
+                        format!(
+                            "variable ‘{}’ has type ‘{}’, found ‘{}’",
+                            var.name, var.ty, found
+                        ),
+                    ),
+                ));
+            }
+        }
+        Statement::AssignField {
+            var_no,
+            fields,
+            value,
+            ..
+        } => {
+            check_expression(value, ns);
+
+            // the innermost field, its name is used in the message
+            let var = &function.vars[*var_no];
+            let (mut name, mut ty) = (var.name.clone(), var.ty.clone());
+            for field_no in fields {
+                if let Type::Struct { struct_no, .. } = ty {
+                    let field = &ns.structs[struct_no].fields[*field_no];
+                    name = field.name.clone();
+                    ty = field.ty.clone();
+                }
+            }
+
+            let found = value.ty();
+            if !converts(&found, &ty) {
+                ns.diagnostics.push(Diagnostic::type_error(
+                    value.location().unwrap_or(var.location),
+                    mismatch(
+                        value,
+                        &ty,
+                        format!("field ‘{}’ has type ‘{}’, found ‘{}’", name, ty, found),
+                    ),
+                ));
+            }
+        }
+        Statement::Expression { expression, .. } => check_expression(expression, ns),
+        Statement::Return { location, value } => {
+            if let Some(value) = value {
+                check_expression(value, ns);
+            }
+
+            check_return(location, value, function, ns);
+        }
+        Statement::If {
+            cond,
+            then,
+            otherwise,
+            ..
+        } => {
+            check_condition("if", cond, ns);
+
+            for stmt in then.iter().chain(otherwise) {
+                statement(stmt, function, ns);
+            }
+        }
+        Statement::While { cond, body, .. } => {
+            check_condition("while", cond, ns);
+
+            for stmt in body {
+                statement(stmt, function, ns);
+            }
+        }
+        Statement::For {
+            init,
+            cond,
+            next,
+            body,
+            ..
+        } => {
+            if let Some(cond) = cond {
+                check_condition("for", cond, ns);
+            }
+
+            for stmt in init.iter().chain(next).chain(body) {
+                statement(stmt, function, ns);
+            }
+        }
+        Statement::Loop { body, .. } => {
+            for stmt in body {
+                statement(stmt, function, ns);
+            }
+        }
+        Statement::Break { .. } | Statement::Continue { .. } => {}
+    }
+}
+
+fn check_condition(keyword: &str, cond: &Expression, ns: &mut Namespace) {
+    check_expression(cond, ns);
+
+    let found = cond.ty();
+    if found != Type::Bool && !is_unresolved(&found) {
+        if let Some(location) = cond.location() {
+            ns.diagnostics.push(Diagnostic::type_error(
+                location,
+                format!(
+                    "condition of ‘{}’ must be ‘bool’, found ‘{}’",
+                    keyword, found
+                ),
+            ));
+        }
+    }
+}
+
+fn check_return(
+    location: &Loc,
+    value: &Option<Expression>,
+    function: &Function,
+    ns: &mut Namespace,
+) {
+    let declared = function.returns.first().map(|ret| &ret.ty);
+
+    match (declared, value) {
+        (None, None) => {}
+        (None, Some(value)) if !is_unresolved(&value.ty()) => {
+            ns.diagnostics.push(Diagnostic::type_error(
+                value.location().unwrap_or(*location),
+                format!(
+                    "function ‘{}’ has no return type, but returns ‘{}’",
+                    function.name,
+                    value.ty()
+                ),
+            ));
+        }
+        (None, Some(_)) => {}
+        (Some(declared), _) if is_unresolved(declared) => {}
+        (Some(declared), None) => {
+            ns.diagnostics.push(Diagnostic::type_error(
+                *location,
+                format!(
+                    "function ‘{}’ must return a value of type ‘{}’",
+                    function.name, declared
+                ),
+            ));
+        }
+        (Some(declared), Some(value)) => {
+            let found = value.ty();
+            if !converts(&found, declared) {
+                ns.diagnostics.push(Diagnostic::type_error(
+                    value.location().unwrap_or(*location),
+                    mismatch(
+                        value,
+                        declared,
+                        format!(
+                            "function ‘{}’ returns ‘{}’, found ‘{}’",
+                            function.name, declared, found
+                        ),
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+fn check_expression(expr: &Expression, ns: &mut Namespace) {
+    match expr {
+        Expression::InternalFunctionCall {
+            location,
+            function,
+            args,
+            ..
+        } => {
+            for arg in args {
+                check_expression(arg, ns);
+            }
+
+            if let Expression::InternalFunction { function_no, .. } = function.as_ref() {
+                check_call(location, *function_no, args, ns);
+            }
+        }
+        Expression::Builtin { args, .. } => {
+            // the overload is picked by argument types already, only check nested calls
+            for arg in args {
+                check_expression(arg, ns);
+            }
+        }
+        // operand types are checked when resolving operators
+        Expression::Binary { left, right, .. } => {
+            check_expression(left, ns);
+            check_expression(right, ns);
+        }
+        Expression::Unary { expr, .. }
+        | Expression::Cast { expr, .. }
+        | Expression::StructMember { expr, .. } => check_expression(expr, ns),
+        Expression::StructLiteral {
+            location,
+            ty,
+            values,
+        } => {
+            for value in values {
+                check_expression(value, ns);
+            }
+
+            if let Type::Struct { struct_no, name } = ty {
+                check_fields(location, *struct_no, name, values, ns);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn check_fields(
+    location: &Loc,
+    struct_no: usize,
+    name: &str,
+    values: &[Expression],
+    ns: &mut Namespace,
+) {
+    let fields = ns.structs[struct_no].fields.clone();
+
+    for (field, value) in fields.iter().zip(values) {
+        let found = value.ty();
+        if !converts(&found, &field.ty) {
+            ns.diagnostics.push(Diagnostic::type_error(
+                value.location().unwrap_or(*location),
+                mismatch(
+                    value,
+                    &field.ty,
+                    format!(
+                        "field ‘{}’ of ‘{}’ has type ‘{}’, found ‘{}’",
+                        field.name, name, field.ty, found
+                    ),
+                ),
+            ));
+        }
+    }
+}
+
+fn check_call(location: &Loc, function_no: usize, args: &[Expression], ns: &mut Namespace) {
+    let callee = ns.functions[function_no].clone();
+
+    if callee.params.len() != args.len() {
+        // the receiver of a method is not written as an argument
+        let receiver = callee.is_method() as usize;
+        ns.diagnostics.push(Diagnostic::type_error(
+            *location,
+            format!(
+                "function ‘{}’ expects {} arguments, {} provided",
+                callee.name,
+                callee.params.len() - receiver,
+                args.len() - receiver
+            ),
+        ));
+        return;
+    }
+
+    for (param, arg) in callee.params.iter().zip(args) {
+        let found = arg.ty();
+        if !converts(&found, &param.ty) {
+            ns.diagnostics.push(Diagnostic::type_error(
+                arg.location().unwrap_or(*location),
+                mismatch(
+                    arg,
+                    &param.ty,
+                    format!(
+                        "argument ‘{}’ of ‘{}’ expects ‘{}’, found ‘{}’",
+                        param.name, callee.name, param.ty, found
+                    ),
+                ),
+            ));
+        }
+    }
+}
+
+/// Whether a value of type `found` can be used as `expected`, an unresolved type on either
+/// side is reported already
+fn converts(found: &Type, expected: &Type) -> bool {
+    is_unresolved(found) || is_unresolved(expected) || found.can_convert_to(expected)
+}
+
+/// A number literal of a number type it does not fit in is reported by its value
+fn mismatch(value: &Expression, expected: &Type, message: String) -> String {
+    match value {
+        Expression::NumberLiteral { value, .. } if expected.is_number() => {
+            format!("number literal ‘{}’ does not fit in ‘{}’", value, expected)
+        }
+        _ => message,
+    }
+}
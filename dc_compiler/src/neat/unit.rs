@@ -1,69 +1,79 @@
-use dc_parser::{Program, ProgramUnit, StructFuncDecl};
+use dc_lexer::Loc;
+use dc_parser::{Program, ProgramUnit};
 
+use crate::neat::function::function_decl;
+use crate::neat::object::object_decl;
+use crate::neat::struct_decl::resolve_structs;
 use crate::neat::struct_function::struct_function_decl;
+use crate::neat::type_check::type_check;
 use crate::neat::{statements, Namespace};
 
-pub fn resolve_program(program: Program, namespace: &mut Namespace) {
-    // todo: make structs
-    let _structs = program
-        .0
+/// Resolve the files of one compilation together: every declaration is known before the
+/// first function body is resolved, so functions can call each other across files.
+pub fn resolve_programs(programs: &[Program], namespace: &mut Namespace) {
+    let units = programs
         .iter()
-        .filter_map(|part| {
-            if let ProgramUnit::StructDecl(def) = part {
-                Some(def)
-            } else {
-                None
-            }
-        })
-        .enumerate()
-        .map(|(no, def)| (no, def.as_ref()))
-        .collect::<Vec<(usize, &dc_parser::StructDecl)>>();
+        .flat_map(|program| program.0.iter())
+        .collect::<Vec<&ProgramUnit>>();
 
-    // todo: resolve struct function
-    let struct_funcs = program
-        .0
+    let structs = units
         .iter()
         .filter_map(|part| {
-            if let ProgramUnit::StructFuncDecl(def) = part {
-                Some(def)
+            if let ProgramUnit::StructDecl(def) = part {
+                Some(def.as_ref())
             } else {
                 None
             }
         })
-        .enumerate()
-        .map(|(no, def)| (no, def.as_ref()))
-        .collect::<Vec<(usize, &dc_parser::StructFuncDecl)>>();
+        .collect::<Vec<&dc_parser::StructDecl>>();
 
-    // todo: add import support
-    for part in &program.0 {
-        match part {
-            ProgramUnit::ImportDecl(_) => {}
-            _ => {}
-        }
-    }
+    resolve_structs(&structs, namespace);
+    resolve_functions(&units, namespace);
 
-    resolve_struct_functions(struct_funcs, namespace);
+    type_check(namespace);
 }
 
-pub fn resolve_struct_functions(
-    struct_funcs: Vec<(usize, &StructFuncDecl)>,
-    namespace: &mut Namespace,
-) -> bool {
-    let mut _broken = false;
-    let mut function_bodies = Vec::new();
+/// Declare the functions, methods and objects in the order of the sourcecode,
+/// then resolve their bodies.
+pub fn resolve_functions(units: &[&ProgramUnit], namespace: &mut Namespace) -> bool {
+    let mut broken = false;
+    let mut function_bodies: Vec<(usize, &[dc_parser::Statement], Loc)> = Vec::new();
 
-    for (index, func) in struct_funcs {
-        struct_function_decl(func, namespace);
-        if !(func.body.is_empty()) {
-            function_bodies.push((index, func));
-        } else {
-            // todo
+    for part in units {
+        match part {
+            ProgramUnit::StructFuncDecl(def) => match struct_function_decl(def, namespace) {
+                Some(function_no) => function_bodies.push((function_no, &def.body, def.loc)),
+                None => broken = true,
+            },
+            ProgramUnit::FuncDecl(def) => match function_decl(def, None, namespace) {
+                Some(function_no) => function_bodies.push((function_no, &def.body, def.loc)),
+                None => broken = true,
+            },
+            ProgramUnit::ObjectDecl(def) => {
+                let object_no = match object_decl(def, namespace) {
+                    Some(object_no) => object_no,
+                    None => {
+                        broken = true;
+                        continue;
+                    }
+                };
+
+                for func in &def.functions {
+                    match function_decl(func, Some(object_no), namespace) {
+                        Some(function_no) => {
+                            function_bodies.push((function_no, &func.body, func.loc))
+                        }
+                        None => broken = true,
+                    }
+                }
+            }
+            _ => {}
         }
     }
 
-    for (index, def) in function_bodies {
-        statements::resolve_function_body(def, namespace, index);
+    for (function_no, body, location) in function_bodies {
+        statements::resolve_function_body(body, location, namespace, function_no);
     }
 
-    _broken
+    broken
 }
@@ -1,46 +1,159 @@
-use dc_lexer::Location;
+use dc_lexer::Loc;
 use num_bigint::BigInt;
 
-use crate::Type;
+use crate::{BinOpKind, Type, UnOpKind};
 
 #[derive(PartialEq, Clone, Debug)]
 pub enum Builtin {
     Assert,
+    /// print the value, without a new line
     Print,
+    /// print the value and a new line
+    Println,
+    /// a builtin of the embedding program, by its number in `Namespace::builtins`
+    Host(usize),
 }
 
 #[derive(Clone, Debug)]
 pub enum Expression {
     Placeholder,
     Variable {
-        location: Location,
+        location: Loc,
         ty: Type,
-        // change to symbol table
-        value: String,
+        var_no: usize,
+    },
+    InternalFunction {
+        location: Loc,
+        function_no: usize,
+    },
+    BoolLiteral {
+        location: Loc,
+        value: bool,
     },
     StringLiteral {
-        location: Location,
+        location: Loc,
         value: String,
     },
     NumberLiteral {
-        location: Location,
+        location: Loc,
         ty: Type,
         value: BigInt,
     },
+    FloatLiteral {
+        location: Loc,
+        ty: Type,
+        value: f64,
+    },
     BytesLiteral {
-        location: Location,
+        location: Loc,
         ty: Type,
         value: Vec<u8>,
     },
+    Binary {
+        location: Loc,
+        ty: Type,
+        op: BinOpKind,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+    Unary {
+        location: Loc,
+        ty: Type,
+        op: UnOpKind,
+        expr: Box<Expression>,
+    },
+    /// `x++`, the value is the one before the increment
+    PostIncrement {
+        location: Loc,
+        ty: Type,
+        var_no: usize,
+    },
+    /// `x--`, the value is the one before the decrement
+    PostDecrement {
+        location: Loc,
+        ty: Type,
+        var_no: usize,
+    },
+    /// Convert the value to another type, e.g. sign extend an integer
+    Cast {
+        location: Loc,
+        to: Type,
+        expr: Box<Expression>,
+    },
+    /// The values of all fields, in the order of declaration
+    StructLiteral {
+        location: Loc,
+        ty: Type,
+        values: Vec<Expression>,
+    },
+    /// `value.name`, the field is numbered in the order of declaration
+    StructMember {
+        location: Loc,
+        ty: Type,
+        expr: Box<Expression>,
+        field: usize,
+    },
     InternalFunctionCall {
-        location: Location,
+        location: Loc,
+        returns: Vec<Type>,
         function: Box<Expression>,
         args: Vec<Expression>,
     },
     Builtin {
-        location: Location,
+        location: Loc,
         types: Vec<Type>,
         builtin: Builtin,
         args: Vec<Expression>,
     },
 }
+
+impl Expression {
+    /// The type of the value, `Type::Void` when there is none.
+    pub fn ty(&self) -> Type {
+        match self {
+            Expression::Placeholder => Type::Void,
+            Expression::Variable { ty, .. } => ty.clone(),
+            Expression::InternalFunction { .. } => Type::Void,
+            Expression::BoolLiteral { .. } => Type::Bool,
+            Expression::StringLiteral { .. } => Type::String,
+            Expression::NumberLiteral { ty, .. } | Expression::FloatLiteral { ty, .. } => {
+                ty.clone()
+            }
+            Expression::BytesLiteral { ty, .. } => ty.clone(),
+            Expression::Binary { ty, .. } | Expression::Unary { ty, .. } => ty.clone(),
+            Expression::PostIncrement { ty, .. } | Expression::PostDecrement { ty, .. } => {
+                ty.clone()
+            }
+            Expression::Cast { to, .. } => to.clone(),
+            Expression::StructLiteral { ty, .. } | Expression::StructMember { ty, .. } => {
+                ty.clone()
+            }
+            Expression::InternalFunctionCall { returns, .. } => {
+                returns.first().cloned().unwrap_or(Type::Void)
+            }
+            Expression::Builtin { types, .. } => types.first().cloned().unwrap_or(Type::Void),
+        }
+    }
+
+    pub fn location(&self) -> Option<Loc> {
+        match self {
+            Expression::Placeholder => None,
+            Expression::Variable { location, .. }
+            | Expression::InternalFunction { location, .. }
+            | Expression::BoolLiteral { location, .. }
+            | Expression::StringLiteral { location, .. }
+            | Expression::NumberLiteral { location, .. }
+            | Expression::FloatLiteral { location, .. }
+            | Expression::BytesLiteral { location, .. }
+            | Expression::Binary { location, .. }
+            | Expression::Unary { location, .. }
+            | Expression::PostIncrement { location, .. }
+            | Expression::PostDecrement { location, .. }
+            | Expression::Cast { location, .. }
+            | Expression::StructLiteral { location, .. }
+            | Expression::StructMember { location, .. }
+            | Expression::InternalFunctionCall { location, .. }
+            | Expression::Builtin { location, .. } => Some(*location),
+        }
+    }
+}
@@ -1,20 +1,68 @@
-use crate::{Parameter, Statement};
+use crate::{Parameter, Statement, Type, Variable};
+use dc_lexer::Loc;
 
 #[derive(Clone, Debug)]
 pub struct Function {
+    pub location: Loc,
+    /// the imported package which declares the function, `None` for the program itself
+    pub package: Option<String>,
+    /// the struct of a method, its value is passed as the first parameter `self`
+    pub receiver: Option<Type>,
+    /// `Summary$new`, called as `Summary.new(...)` or `Summary(...)`. It has no `self`
+    /// parameter, the new value is the local variable `self`, which is returned.
+    pub constructor: bool,
+    /// the object which declares the function, which is called as `Object.name(...)`
+    pub object: Option<String>,
     pub name: String,
     pub params: Vec<Parameter>,
     pub returns: Vec<Parameter>,
     pub body: Vec<Statement>,
+    /// parameters followed by the local variables of the body
+    pub vars: Vec<Variable>,
 }
 
 impl Function {
-    pub fn new(name: String, params: Vec<Parameter>, returns: Vec<Parameter>) -> Self {
+    pub fn new(
+        location: Loc,
+        package: Option<String>,
+        receiver: Option<Type>,
+        name: String,
+        params: Vec<Parameter>,
+        returns: Vec<Parameter>,
+    ) -> Self {
         Function {
+            location,
+            package,
+            receiver,
+            constructor: false,
+            object: None,
             name,
             params,
             returns,
             body: Vec::new(),
+            vars: Vec::new(),
+        }
+    }
+
+    /// A method of a struct, which gets the value it is called on by reference, as its first
+    /// parameter `self`. Assigning the fields of `self` changes the value of the caller.
+    pub fn is_method(&self) -> bool {
+        self.receiver.is_some() && !self.constructor
+    }
+
+    /// The name of the function in the generated code, qualified by its package.
+    /// Methods are prefixed by their struct, like `Summary$analysis`, and the functions
+    /// of an object by the object, like `Math.max`.
+    pub fn symbol_name(&self) -> String {
+        let name = match (&self.receiver, &self.object) {
+            (Some(receiver), _) => format!("{}${}", receiver, self.name),
+            (None, Some(object)) => format!("{}.{}", object, self.name),
+            (None, None) => self.name.clone(),
+        };
+
+        match &self.package {
+            Some(package) => format!("{}.{}", package, name),
+            None => name,
         }
     }
 }
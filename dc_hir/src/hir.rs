@@ -1,9 +1,11 @@
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug)]
 pub struct Expr<'hir> {
     pub kind: ExprKind<'hir>,
 }
 
-#[derive(Copy, Clone, PartialEq, Debug)]
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum BinOpKind {
     /// The `+` operator (addition).
     Add,
@@ -43,6 +45,14 @@ pub enum BinOpKind {
     Gt,
 }
 
+#[derive(Copy, Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum UnOpKind {
+    /// The `!` and `~` operators, logical negation of a `bool`, complement of an integer.
+    Not,
+    /// The `-` operator (negation).
+    Neg,
+}
+
 #[derive(Debug)]
 pub enum ExprKind<'hir> {
     Call(&'hir Expr<'hir>, &'hir [Expr<'hir>]),
@@ -2,6 +2,7 @@ use dc_lexer::Loc;
 pub use expression::*;
 pub use function::*;
 pub use hir::*;
+pub use object::*;
 pub use statement::*;
 pub use struct_def::*;
 pub use types::*;
@@ -9,12 +10,22 @@ pub use types::*;
 pub mod expression;
 pub mod function;
 pub mod hir;
+pub mod object;
 pub mod statement;
 pub mod struct_def;
 pub mod types;
 
+/// A local variable or parameter, numbered by its position in `Function::vars`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variable {
+    pub location: Loc,
+    pub name: String,
+    pub ty: Type,
+}
+
 #[derive(Clone, Debug)]
 pub struct Parameter {
     pub location: Loc,
     pub name: String,
+    pub ty: Type,
 }
@@ -0,0 +1,12 @@
+use dc_lexer::Loc;
+
+/// `object Name { fun ... }`, a singleton whose functions are called as `Name.function()`
+#[derive(Clone, Debug)]
+pub struct ObjectDecl {
+    pub location: Loc,
+    /// the imported package which declares the object, `None` for the program itself
+    pub package: Option<String>,
+    pub name: String,
+    /// the functions, by their number in the functions of the namespace
+    pub functions: Vec<usize>,
+}
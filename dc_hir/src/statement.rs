@@ -1,13 +1,68 @@
 use crate::Expression;
-use dc_lexer::Location;
+use dc_lexer::Loc;
 
 #[derive(Clone, Debug)]
 pub enum Statement {
     VariableDecl {
-        location: Location,
+        location: Loc,
+        var_no: usize,
+        value: Expression,
     },
     Expression {
-        location: Location,
+        location: Loc,
         expression: Expression,
     },
+    /// assign a new value to a declared variable
+    Assign {
+        location: Loc,
+        var_no: usize,
+        value: Expression,
+    },
+    /// `a.b.c = value`, the fields are numbered from the outermost struct inwards
+    AssignField {
+        location: Loc,
+        var_no: usize,
+        fields: Vec<usize>,
+        value: Expression,
+    },
+    Return {
+        location: Loc,
+        value: Option<Expression>,
+    },
+    If {
+        location: Loc,
+        cond: Expression,
+        then: Vec<Statement>,
+        otherwise: Vec<Statement>,
+    },
+    While {
+        location: Loc,
+        label: Option<String>,
+        cond: Expression,
+        body: Vec<Statement>,
+    },
+    /// `for (init; cond; next) body`, without a condition only `break` ends the loop
+    For {
+        location: Loc,
+        label: Option<String>,
+        init: Vec<Statement>,
+        cond: Option<Expression>,
+        next: Vec<Statement>,
+        body: Vec<Statement>,
+    },
+    /// `loop { ... }`, only `break` or `return` end the loop
+    Loop {
+        location: Loc,
+        label: Option<String>,
+        body: Vec<Statement>,
+    },
+    /// leave the innermost loop, or the loop with the label
+    Break {
+        location: Loc,
+        label: Option<String>,
+    },
+    Continue {
+        location: Loc,
+        label: Option<String>,
+    },
 }
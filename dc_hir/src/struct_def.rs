@@ -1,4 +1,6 @@
-use crate::Function;
+use dc_lexer::Loc;
+
+use crate::Type;
 
 #[derive(Clone, Debug)]
 pub struct Struct {
@@ -7,6 +9,25 @@ pub struct Struct {
 
 #[derive(Clone, Debug)]
 pub struct StructDecl {
+    pub location: Loc,
+    /// the imported package which declares the struct, `None` for the program itself
+    pub package: Option<String>,
+    pub name: String,
+    pub fields: Vec<StructField>,
+    /// the methods, by their number in the functions of the namespace
+    pub functions: Vec<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StructField {
+    pub location: Loc,
     pub name: String,
-    pub functions: Vec<Function>,
+    pub ty: Type,
+}
+
+impl StructDecl {
+    /// The number of the field, in the order of declaration
+    pub fn field(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
 }
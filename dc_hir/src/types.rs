@@ -1,8 +1,69 @@
-#[derive(PartialEq, Clone, Debug)]
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
 pub enum Type {
     Bool,
     Int(u16),
+    Uint(u16),
+    /// `float32` or `float64`
+    Float(u16),
     Void,
+    /// A declared type which could not be resolved, the error is reported already
+    Unresolved,
     String,
     Bytes(u8),
+    /// A struct of the namespace, the name is kept for messages
+    Struct {
+        struct_no: usize,
+        name: String,
+    },
+}
+
+impl Type {
+    /// Whether a value of this type can be used where `to` is expected, numbers are
+    /// widened implicitly: unsigned integers also to a wider signed type, signed ones never
+    /// to an unsigned type, and integers never to floats.
+    pub fn can_convert_to(&self, to: &Type) -> bool {
+        match (self, to) {
+            (Type::Int(from), Type::Int(to)) | (Type::Uint(from), Type::Uint(to)) => from <= to,
+            (Type::Uint(from), Type::Int(to)) => from < to,
+            (Type::Float(from), Type::Float(to)) => from <= to,
+            _ => self == to,
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(self, Type::Int(_) | Type::Uint(_))
+    }
+
+    pub fn is_signed_integer(&self) -> bool {
+        matches!(self, Type::Int(_))
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, Type::Float(_))
+    }
+
+    /// Integers and floats, the types of arithmetic
+    pub fn is_number(&self) -> bool {
+        self.is_integer() || self.is_float()
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Type::Bool => write!(f, "bool"),
+            Type::Int(n) => write!(f, "int{}", n),
+            Type::Uint(n) => write!(f, "uint{}", n),
+            Type::Float(n) => write!(f, "float{}", n),
+            Type::Void => write!(f, "void"),
+            Type::Unresolved => write!(f, "unresolved"),
+            Type::String => write!(f, "string"),
+            Type::Bytes(n) => write!(f, "bytes{}", n),
+            Type::Struct { name, .. } => write!(f, "{}", name),
+        }
+    }
 }
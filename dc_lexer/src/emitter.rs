@@ -0,0 +1,258 @@
+//! Render diagnostics with the offending sourcecode, either for humans or as json for tools.
+use serde_json::json;
+
+use crate::error::{Diagnostic, Level};
+use crate::location::Loc;
+use crate::source_map::SourceMap;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BLUE: &str = "\x1b[1;34m";
+
+fn level_color(level: &Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[1;31m",
+        Level::Warning => "\x1b[1;33m",
+        Level::Info => "\x1b[1;32m",
+        Level::Debug => "\x1b[1;36m",
+    }
+}
+
+struct Painter {
+    color: bool,
+}
+
+impl Painter {
+    fn paint(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+/// Render a diagnostic like rustc does:
+///
+/// ```text
+/// error: unknown function ‘say_hello’
+///  --> hello.cj:2:17
+///   |
+/// 2 | default$main() {say_hello();}
+///   |                 ^^^^^^^^^
+/// ```
+pub fn render(diagnostic: &Diagnostic, source_map: &SourceMap, color: bool) -> String {
+    let painter = Painter { color };
+    let level_style = level_color(&diagnostic.level);
+
+    let mut out = format!(
+        "{}{}\n",
+        painter.paint(level_style, &diagnostic.level.to_string()),
+        painter.paint(BOLD, &format!(": {}", diagnostic.message))
+    );
+
+    let mut locs = vec![];
+    if let Some(pos) = &diagnostic.pos {
+        locs.push(*pos);
+    }
+    locs.extend(diagnostic.notes.iter().map(|note| note.pos));
+
+    let width = locs
+        .iter()
+        .map(|loc| source_map.span(loc).end.row().to_string().len())
+        .max()
+        .unwrap_or(1);
+
+    if let Some(pos) = diagnostic.pos {
+        let label = Label {
+            loc: pos,
+            arrow: "-->",
+            mark: '^',
+            message: "",
+            style: level_style,
+        };
+        out.push_str(&snippet(&label, width, source_map, &painter));
+    }
+
+    for note in &diagnostic.notes {
+        let label = Label {
+            loc: note.pos,
+            arrow: ":::",
+            mark: '-',
+            message: &note.message,
+            style: BLUE,
+        };
+        out.push_str(&snippet(&label, width, source_map, &painter));
+    }
+
+    out
+}
+
+/// A marked span of the source, the primary label is marked with `^`, notes with `-`.
+struct Label<'a> {
+    loc: Loc,
+    arrow: &'static str,
+    mark: char,
+    message: &'a str,
+    style: &'static str,
+}
+
+fn snippet(label: &Label, width: usize, source_map: &SourceMap, painter: &Painter) -> String {
+    let file = source_map.file(label.loc.file_no());
+    let span = source_map.span(&label.loc);
+    let gutter = painter.paint(BLUE, &format!("{} |", " ".repeat(width)));
+
+    let mut out = format!(
+        "{}{} {}:{}:{}\n{}\n",
+        " ".repeat(width),
+        painter.paint(BLUE, label.arrow),
+        file.name,
+        span.start.row(),
+        span.start.column(),
+        gutter
+    );
+
+    for row in span.start.row()..=span.end.row() {
+        let line = file.line(row).unwrap_or("");
+        let chars = line.chars().collect::<Vec<char>>();
+
+        let start = if row == span.start.row() {
+            span.start.column() - 1
+        } else {
+            0
+        };
+        let end = if row == span.end.row() {
+            span.end.column() - 1
+        } else {
+            chars.len()
+        };
+        let end = end.min(chars.len()).max(start + 1);
+
+        // keep tabs so the marks line up with the source line
+        let padding = chars
+            .iter()
+            .take(start)
+            .map(|ch| if *ch == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+        let marks = label.mark.to_string().repeat(end - start);
+        let text = if row == span.end.row() && !label.message.is_empty() {
+            format!("{} {}", marks, label.message)
+        } else {
+            marks
+        };
+
+        out.push_str(&format!(
+            "{} {}\n{} {}{}\n",
+            painter.paint(BLUE, &format!("{:>width$} |", row, width = width)),
+            line,
+            gutter,
+            padding,
+            painter.paint(label.style, &text)
+        ));
+    }
+
+    out
+}
+
+/// A diagnostic as a single line json object, which editors and CI can parse.
+pub fn render_json(diagnostic: &Diagnostic, source_map: &SourceMap) -> String {
+    let notes = diagnostic
+        .notes
+        .iter()
+        .map(|note| {
+            let mut value = json_loc(&note.pos, source_map);
+            value["message"] = json!(note.message);
+            value
+        })
+        .collect::<Vec<serde_json::Value>>();
+
+    let value = json!({
+        "level": diagnostic.level.to_string(),
+        "type": format!("{:?}", diagnostic.ty),
+        "message": diagnostic.message,
+        "location": diagnostic.pos.map(|pos| json_loc(&pos, source_map)),
+        "notes": notes,
+        "rendered": render(diagnostic, source_map, false),
+    });
+
+    value.to_string()
+}
+
+fn json_loc(loc: &Loc, source_map: &SourceMap) -> serde_json::Value {
+    let span = source_map.span(loc);
+
+    json!({
+        "file": source_map.file(loc.file_no()).name,
+        "offset": [loc.start(), loc.end()],
+        "start": { "line": span.start.row(), "column": span.start.column() },
+        "end": { "line": span.end.row(), "column": span.end.column() },
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::emitter::{render, render_json};
+    use crate::error::{Diagnostic, Note};
+    use crate::location::Loc;
+    use crate::source_map::SourceMap;
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_render_source_snippet() {
+        let mut map = SourceMap::new();
+        let file_no = map.add_file("hello.cj", "pkg charj\ndefault$main() {say_hello();}\n");
+        let diagnostic = Diagnostic::decl_error(
+            Loc(file_no, 26, 35),
+            "unknown function ‘say_hello’".to_string(),
+        );
+
+        assert_eq!(render(&diagnostic, &map, false), "error: unknown function ‘say_hello’
+ --> hello.cj:2:17
+  |
+2 | default$main() {say_hello();}
+  |                 ^^^^^^^^^
+");
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn should_render_notes_as_secondary_labels() {
+        let mut map = SourceMap::new();
+        let first = map.add_file("a.cj", "default$main() {}");
+        let second = map.add_file("b.cj", "pkg b\n\tdefault$main() {}");
+        let mut diagnostic = Diagnostic::decl_error(
+            Loc(second, 15, 19),
+            "function ‘main’ is already defined".to_string(),
+        );
+        diagnostic.notes.push(Note {
+            pos: Loc(first, 8, 12),
+            message: "previous definition of ‘main’".to_string(),
+        });
+
+        assert_eq!(render(&diagnostic, &map, false), "error: function ‘main’ is already defined
+ --> b.cj:2:10
+  |
+2 | \tdefault$main() {}
+  | \t        ^^^^
+ ::: a.cj:1:9
+  |
+1 | default$main() {}
+  |         ---- previous definition of ‘main’
+");
+    }
+
+    #[test]
+    fn should_render_json() {
+        let mut map = SourceMap::new();
+        let file_no = map.add_file("hello.cj", "default$main() {\n}");
+        let diagnostic = Diagnostic::parser_error(Loc(file_no, 18, 18), "unexpected".to_string());
+
+        let value: serde_json::Value =
+            serde_json::from_str(&render_json(&diagnostic, &map)).unwrap();
+        assert_eq!("error", value["level"]);
+        assert_eq!("ParserError", value["type"]);
+        assert_eq!("hello.cj", value["location"]["file"]);
+        assert_eq!(2, value["location"]["start"]["line"]);
+        assert_eq!(2, value["location"]["start"]["column"]);
+    }
+}
@@ -13,6 +13,17 @@ pub enum Level {
     Error,
 }
 
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Level::Debug => write!(f, "debug"),
+            Level::Info => write!(f, "info"),
+            Level::Warning => write!(f, "warning"),
+            Level::Error => write!(f, "error"),
+        }
+    }
+}
+
 #[derive(Debug, Eq, Hash, PartialEq)]
 pub enum ErrorType {
     None,
@@ -39,29 +50,35 @@ pub struct Diagnostic {
 }
 
 impl Diagnostic {
-    pub fn handle_error(error: ParseError<usize, Token, LexicalError>) -> Diagnostic {
+    pub fn handle_error(
+        file_no: usize,
+        error: ParseError<usize, Token, LexicalError>,
+    ) -> Diagnostic {
         match error {
-            ParseError::InvalidToken { location } => {
-                Diagnostic::parser_error(Loc(location, location), "invalid token".to_string())
-            }
+            ParseError::InvalidToken { location } => Diagnostic::parser_error(
+                Loc(file_no, location, location),
+                "invalid token".to_string(),
+            ),
             ParseError::UnrecognizedToken {
                 token: (l, token, r),
                 expected,
             } => Diagnostic::parser_error(
-                Loc(l, r),
+                Loc(file_no, l, r),
                 format!(
                     "unrecognised token `{}', expected {}",
                     token,
                     expected.join(", ")
                 ),
             ),
-            ParseError::User { error } => Diagnostic::parser_error(error.loc(), error.to_string()),
+            ParseError::User { error } => {
+                Diagnostic::parser_error(error.loc(file_no), error.to_string())
+            }
             ParseError::ExtraToken { token } => Diagnostic::parser_error(
-                Loc(token.0, token.2),
+                Loc(file_no, token.0, token.2),
                 format!("extra token `{}' encountered", token.0),
             ),
             ParseError::UnrecognizedEOF { location, expected } => Diagnostic::parser_error(
-                Loc(location, location),
+                Loc(file_no, location, location),
                 format!("unexpected end of file, expected {}", expected.join(", ")),
             ),
         }
@@ -86,6 +103,53 @@ impl Diagnostic {
             notes: Vec::new(),
         }
     }
+
+    pub fn decl_error(pos: Loc, message: String) -> Self {
+        Diagnostic {
+            level: Level::Error,
+            ty: ErrorType::DeclarationError,
+            pos: Some(pos),
+            message,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn type_error(pos: Loc, message: String) -> Self {
+        Diagnostic {
+            level: Level::Error,
+            ty: ErrorType::TypeError,
+            pos: Some(pos),
+            message,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn warning(pos: Loc, message: String) -> Self {
+        Diagnostic {
+            level: Level::Warning,
+            ty: ErrorType::Warning,
+            pos: Some(pos),
+            message,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn error_with_note(pos: Loc, message: String, note_pos: Loc, note: String) -> Self {
+        Diagnostic {
+            level: Level::Error,
+            ty: ErrorType::DeclarationError,
+            pos: Some(pos),
+            message,
+            notes: vec![Note {
+                pos: note_pos,
+                message: note,
+            }],
+        }
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.level == Level::Error
+    }
 }
 
 #[derive(Debug, PartialEq)]
@@ -122,16 +186,16 @@ impl fmt::Display for LexicalError {
 }
 
 impl LexicalError {
-    pub fn loc(&self) -> Loc {
+    pub fn loc(&self, file_no: usize) -> Loc {
         match self {
-            LexicalError::EndOfFileInComment(start, end) => Loc(*start, *end),
-            LexicalError::EndOfFileInString(start, end) => Loc(*start, *end),
-            LexicalError::EndOfFileInHex(start, end) => Loc(*start, *end),
-            LexicalError::MissingNumber(start, end) => Loc(*start, *end),
-            LexicalError::InvalidCharacterInHexLiteral(pos, _) => Loc(*pos, *pos),
-            LexicalError::UnrecognisedToken(start, end, _) => Loc(*start, *end),
-            LexicalError::ExpectedFrom(start, end, _) => Loc(*start, *end),
-            LexicalError::MissingExponent(start, end) => Loc(*start, *end),
+            LexicalError::EndOfFileInComment(start, end) => Loc(file_no, *start, *end),
+            LexicalError::EndOfFileInString(start, end) => Loc(file_no, *start, *end),
+            LexicalError::EndOfFileInHex(start, end) => Loc(file_no, *start, *end),
+            LexicalError::MissingNumber(start, end) => Loc(file_no, *start, *end),
+            LexicalError::InvalidCharacterInHexLiteral(pos, _) => Loc(file_no, *pos, *pos),
+            LexicalError::UnrecognisedToken(start, end, _) => Loc(file_no, *start, *end),
+            LexicalError::ExpectedFrom(start, end, _) => Loc(file_no, *start, *end),
+            LexicalError::MissingExponent(start, end) => Loc(file_no, *start, *end),
         }
     }
 }
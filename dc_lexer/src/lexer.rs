@@ -25,11 +25,13 @@ static KEYWORDS: phf::Map<&'static str, Token> = phf_map! {
     "struct" => Token::Struct,
     "as" => Token::As,
     "fun" => Token::Fun,
+    "object" => Token::Object,
 
     // statement
     "if" => Token::If,
     "else" => Token::Else,
     "while" => Token::While,
+    "loop" => Token::Loop,
     "for" => Token::For,
     "in" => Token::In,
     "break" => Token::Break,
@@ -44,12 +46,34 @@ static KEYWORDS: phf::Map<&'static str, Token> = phf_map! {
 
     "string" => Token::String,
     "int" => Token::Int(256),
-    // "int256" => Token::Int(256),
     "uint" => Token::Uint(256),
+    "float32" => Token::Float(32),
+    "float64" => Token::Float(64),
 
     "$" => Token::Binding,
 };
 
+/// `int8` to `int256` and `uint8` to `uint256`, the width is a multiple of 8
+fn sized_integer(id: &str) -> Option<Token<'static>> {
+    let (width, token): (&str, fn(u16) -> Token<'static>) =
+        if let Some(width) = id.strip_prefix("uint") {
+            (width, Token::Uint)
+        } else if let Some(width) = id.strip_prefix("int") {
+            (width, Token::Int)
+        } else {
+            return None;
+        };
+
+    if width.starts_with('0') || !width.chars().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+
+    match width.parse::<u16>() {
+        Ok(bits) if (8..=256).contains(&bits) && bits % 8 == 0 => Some(token(bits)),
+        _ => None,
+    }
+}
+
 impl<'input> Lexer<'input> {
     pub fn new(input: &'input str) -> Self {
         let mut lexer = Lexer {
@@ -157,6 +181,22 @@ impl<'input> Lexer<'input> {
             self.chars.next();
         }
 
+        // a fraction makes a float literal, `0..10` is a range
+        let mut float = false;
+        if let Some((i, '.')) = self.chars.peek() {
+            if self.input[i + 1..].starts_with(|ch: char| ch.is_ascii_digit()) {
+                float = true;
+                self.chars.next();
+                while let Some((i, ch)) = self.chars.peek() {
+                    if !ch.is_ascii_digit() && *ch != '_' {
+                        break;
+                    }
+                    end = *i;
+                    self.chars.next();
+                }
+            }
+        }
+
         let base = &self.input[start..=end];
 
         let mut exp_start = end + 1;
@@ -164,6 +204,18 @@ impl<'input> Lexer<'input> {
         if let Some((i, 'e')) = self.chars.peek() {
             exp_start = i + 1;
             self.chars.next();
+            // so does a negative exponent, `1e+5` is an integer like `1e5`
+            match self.chars.peek() {
+                Some((_, '-')) => {
+                    float = true;
+                    self.chars.next();
+                }
+                Some((i, '+')) => {
+                    exp_start = i + 1;
+                    self.chars.next();
+                }
+                _ => {}
+            }
             while let Some((i, ch)) = self.chars.peek() {
                 if !ch.is_ascii_digit() && *ch != '_' {
                     break;
@@ -177,6 +229,14 @@ impl<'input> Lexer<'input> {
             }
         }
 
+        if float {
+            return Some(Ok((
+                start,
+                Token::FloatLiteral(&self.input[start..=end]),
+                end + 1,
+            )));
+        }
+
         let exp = &self.input[exp_start..=end];
 
         Some(Ok((start, Token::NumberLiteral(base, exp), end + 1)))
@@ -247,6 +307,8 @@ impl<'input> Lexer<'input> {
 
                     return if let Some(w) = KEYWORDS.get(id) {
                         Some(Ok((start, *w, end)))
+                    } else if let Some(w) = sized_integer(id) {
+                        Some(Ok((start, w, end)))
                     } else {
                         Some(Ok((start, Token::Identifier(id), end)))
                     };
@@ -491,6 +553,32 @@ impl<'input> Lexer<'input> {
                         _ => Some(Ok((i, Token::Member, i + 1))),
                     };
                 }
+                Some((start, '\'')) => {
+                    // a loop label, `'outer`
+                    let mut end = start + 1;
+                    while let Some((i, ch)) = self.chars.peek() {
+                        let valid = if *i == start + 1 {
+                            *ch == '_' || UnicodeXID::is_xid_start(*ch)
+                        } else {
+                            UnicodeXID::is_xid_continue(*ch)
+                        };
+                        if !valid {
+                            break;
+                        }
+                        end = *i + ch.len_utf8();
+                        self.chars.next();
+                    }
+
+                    if end == start + 1 {
+                        return Some(Err(LexicalError::UnrecognisedToken(
+                            start,
+                            end,
+                            "'".to_owned(),
+                        )));
+                    }
+
+                    return Some(Ok((start, Token::Label(&self.input[start + 1..end]), end)));
+                }
                 Some((i, '[')) => return Some(Ok((i, Token::OpenBracket, i + 1))),
                 Some((i, ']')) => return Some(Ok((i, Token::CloseBracket, i + 1))),
                 Some((i, ':')) => return Some(Ok((i, Token::Colon, i + 1))),
@@ -544,3 +632,22 @@ impl<'input> Iterator for Lexer<'input> {
         token
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::lexer::Lexer;
+    use crate::token::Token;
+
+    fn tokens(input: &str) -> Vec<Token<'_>> {
+        Lexer::new(input).map(|token| token.unwrap().1).collect()
+    }
+
+    #[test]
+    fn should_lex_number_literals() {
+        assert_eq!(vec![Token::NumberLiteral("1", "5")], tokens("1e5"));
+        assert_eq!(vec![Token::NumberLiteral("1", "5")], tokens("1e+5"));
+        assert_eq!(vec![Token::FloatLiteral("2.0e-3")], tokens("2.0e-3"));
+        assert_eq!(vec![Token::FloatLiteral("1e-3")], tokens("1e-3"));
+        assert_eq!(vec![Token::FloatLiteral("1.5e+2")], tokens("1.5e+2"));
+    }
+}
@@ -1,9 +1,13 @@
+pub use emitter::*;
 pub use error::*;
 pub use lexer::*;
 pub use location::*;
+pub use source_map::*;
 pub use token::*;
 
+pub mod emitter;
 pub mod error;
 pub mod lexer;
 pub mod location;
+pub mod source_map;
 pub mod token;
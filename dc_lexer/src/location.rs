@@ -1,16 +1,31 @@
 //! Datatypes to support source location information.
 use std::fmt;
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-pub struct Loc(pub usize, pub usize);
+use serde::{Deserialize, Serialize};
+
+/// A span of the sourcecode: file number, start and end byte offsets.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Loc(pub usize, pub usize, pub usize);
 
 impl Loc {
-    pub fn new(start: usize, end: usize) -> Self {
-        Loc(start, end)
+    pub fn new(file_no: usize, start: usize, end: usize) -> Self {
+        Loc(file_no, start, end)
+    }
+
+    pub fn file_no(&self) -> usize {
+        self.0
+    }
+
+    pub fn start(&self) -> usize {
+        self.1
+    }
+
+    pub fn end(&self) -> usize {
+        self.2
     }
 }
 
-/// A location somewhere in the sourcecode.
+/// A location somewhere in the sourcecode, both line and column are 1-based.
 #[derive(Clone, Copy, Debug, Default, PartialEq)]
 pub struct Location {
     row: usize,
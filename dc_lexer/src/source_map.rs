@@ -0,0 +1,160 @@
+//! Keep the sourcecode of every compiled file, so byte offsets can be turned into lines and columns.
+use crate::location::{Loc, Location};
+
+/// A range in a file, resolved from a `Loc`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Span {
+    pub file_no: usize,
+    pub start: Location,
+    pub end: Location,
+}
+
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    pub name: String,
+    pub source: String,
+    /// byte offset of the first character of each line
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: &str, source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(i, _)| i + 1));
+
+        SourceFile {
+            name: name.to_string(),
+            source: source.to_string(),
+            line_starts,
+        }
+    }
+
+    /// Convert a byte offset into a line and column, columns are counted in characters
+    /// so that utf-8 identifiers line up with what the editor shows.
+    pub fn location(&self, offset: usize) -> Location {
+        let mut offset = offset.min(self.source.len());
+        while !self.source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        let column = self.source[self.line_starts[line]..offset].chars().count();
+
+        Location::new(line + 1, column + 1)
+    }
+
+    /// The text of the given 1-based line, without the line ending.
+    pub fn line(&self, row: usize) -> Option<&str> {
+        let start = *self.line_starts.get(row.checked_sub(1)?)?;
+        let end = self
+            .line_starts
+            .get(row)
+            .map(|next| next - 1)
+            .unwrap_or_else(|| self.source.len());
+
+        Some(self.source[start..end].trim_end_matches('\r'))
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+}
+
+/// All files of a compilation, a file number is the index of the file in the map.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap { files: vec![] }
+    }
+
+    /// Register a file and return its file number
+    pub fn add_file(&mut self, name: &str, source: &str) -> usize {
+        self.files.push(SourceFile::new(name, source));
+        self.files.len() - 1
+    }
+
+    pub fn file(&self, file_no: usize) -> &SourceFile {
+        &self.files[file_no]
+    }
+
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    pub fn span(&self, loc: &Loc) -> Span {
+        let file = self.file(loc.file_no());
+
+        Span {
+            file_no: loc.file_no(),
+            start: file.location(loc.start()),
+            end: file.location(loc.end()),
+        }
+    }
+
+    /// Format a loc as `filename:line:column`
+    pub fn describe(&self, loc: &Loc) -> String {
+        let span = self.span(loc);
+
+        format!(
+            "{}:{}:{}",
+            self.file(loc.file_no()).name,
+            span.start.row(),
+            span.start.column()
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::location::{Loc, Location};
+    use crate::source_map::SourceMap;
+
+    #[test]
+    fn should_hand_out_file_numbers() {
+        let mut map = SourceMap::new();
+        assert_eq!(0, map.add_file("a.cj", "pkg a"));
+        assert_eq!(1, map.add_file("b.cj", "pkg b"));
+        assert_eq!("b.cj", map.file(1).name);
+    }
+
+    #[test]
+    fn should_convert_offsets_to_lines() {
+        let mut map = SourceMap::new();
+        let file_no = map.add_file("a.cj", "pkg charj\nstruct IO {}\n");
+
+        let span = map.span(&Loc(file_no, 10, 16));
+        assert_eq!(Location::new(2, 1), span.start);
+        assert_eq!(Location::new(2, 7), span.end);
+        assert_eq!("a.cj:2:1", map.describe(&Loc(file_no, 10, 16)));
+        assert_eq!(Some("struct IO {}"), map.file(file_no).line(2));
+        assert_eq!(Some(""), map.file(file_no).line(3));
+        assert_eq!(None, map.file(file_no).line(4));
+    }
+
+    #[test]
+    fn should_count_utf8_columns_in_chars() {
+        let mut map = SourceMap::new();
+        let source = "default$主要() {\r\n    显示(\"hello\");\r\n}";
+        let file_no = map.add_file("a.cj", source);
+
+        let offset = source.find("(\"hello").unwrap();
+        let span = map.span(&Loc(file_no, offset, offset + 1));
+        assert_eq!(Location::new(2, 7), span.start);
+        assert_eq!(Some("    显示(\"hello\");"), map.file(file_no).line(2));
+    }
+}
@@ -4,8 +4,13 @@ use std::fmt::{self};
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum Token<'input> {
     Identifier(&'input str),
+    /// `'outer`, without the quote
+    Label(&'input str),
     StringLiteral(&'input str),
     NumberLiteral(&'input str, &'input str),
+    /// `1.5` or `2.0e-3`, a number with a fraction or a negative exponent. `1e+5` is a
+    /// `NumberLiteral` like `1e5`.
+    FloatLiteral(&'input str),
     HexLiteral(&'input str),
     HexNumber(&'input str),
 
@@ -20,6 +25,7 @@ pub enum Token<'input> {
     If,
     Else,
     While,
+    Loop,
     For,
     In,
     Range,
@@ -38,6 +44,7 @@ pub enum Token<'input> {
     String,
     Uint(u16),
     Int(u16),
+    Float(u16),
     Bytes(u8),
     DynamicBytes,
 
@@ -108,10 +115,12 @@ impl<'input> fmt::Display for Token<'input> {
         use Token::*;
         match self {
             Identifier(id) => write!(f, "{}", id),
+            Label(label) => write!(f, "'{}", label),
             StringLiteral(s) => write!(f, "\"{}\"", s),
             HexLiteral(hex) => write!(f, "{}", hex),
             NumberLiteral(base, exp) if exp.is_empty() => write!(f, "{}", base),
             NumberLiteral(base, exp) => write!(f, "{}e{}", base, exp),
+            FloatLiteral(n) => write!(f, "{}", n),
             HexNumber(n) => write!(f, "{}", n),
 
             DocComment(CommentType::Line, s) => write!(f, "///{}", s),
@@ -127,6 +136,7 @@ impl<'input> fmt::Display for Token<'input> {
             If => write!(f, "if"),
             Else => write!(f, "else"),
             While => write!(f, "while"),
+            Loop => write!(f, "loop"),
             For => write!(f, "for"),
             In => write!(f, "in"),
             Range => write!(f, ".."),
@@ -145,6 +155,7 @@ impl<'input> fmt::Display for Token<'input> {
             String => write!(f, "string"),
             Uint(w) => write!(f, "uint{}", w),
             Int(w) => write!(f, "int{}", w),
+            Float(w) => write!(f, "float{}", w),
             Bytes(w) => write!(f, "bytes{}", w),
             DynamicBytes => write!(f, "bytes"),
 
@@ -1,11 +1,12 @@
-use crate::instruction::ExprKind;
+use crate::instruction::{ExprKind, TerminatorKind};
 
 /// which is [basic block](https://en.wikipedia.org/wiki/Basic_block)
 #[derive(Clone, Debug)]
 pub struct BasicBlock {
     pub name: String,
-    //  todo: ConditionKind ?
     pub instructions: Vec<ExprKind>,
+    /// `None` only while the block is being built
+    pub terminator: Option<TerminatorKind>,
 }
 
 impl Default for BasicBlock {
@@ -13,8 +14,20 @@ impl Default for BasicBlock {
         BasicBlock {
             name: "".to_string(),
             instructions: vec![],
+            terminator: None,
         }
     }
 }
 
-impl BasicBlock {}
+impl BasicBlock {
+    pub fn new(name: &str) -> Self {
+        BasicBlock {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.terminator.is_some()
+    }
+}
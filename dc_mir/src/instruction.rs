@@ -1,3 +1,5 @@
+use dc_hir::{BinOpKind, Type, UnOpKind};
+use dc_lexer::Loc;
 use num_bigint::BigInt;
 use serde::{Deserialize, Serialize};
 
@@ -9,18 +11,154 @@ pub enum Constant {
     String { value: String },
 }
 
+/// A value used by an instruction
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
-pub enum MIRKind {
-    Call {},
-    Return,
-    JMP,
+pub enum Operand {
+    /// the current value of a variable, loaded from its stack slot
+    Load {
+        location: Loc,
+        ty: Type,
+        var_no: usize,
+    },
+    Constant {
+        location: Loc,
+        ty: Type,
+        value: Constant,
+    },
+    /// the stack slot of a variable, or of a field of the struct in it, which holds a value
+    /// of type `ty`. A method gets its receiver this way.
+    Address {
+        location: Loc,
+        ty: Type,
+        var_no: usize,
+        fields: Vec<usize>,
+    },
+}
+
+impl Operand {
+    pub fn ty(&self) -> &Type {
+        match self {
+            Operand::Load { ty, .. }
+            | Operand::Constant { ty, .. }
+            | Operand::Address { ty, .. } => ty,
+        }
+    }
+
+    pub fn location(&self) -> Loc {
+        match self {
+            Operand::Load { location, .. }
+            | Operand::Constant { location, .. }
+            | Operand::Address { location, .. } => *location,
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExprKind {
-    Var { value: String },
-    Call { value: String },
-    Print { value: String },
+    Var {
+        location: Loc,
+        value: String,
+    },
+    /// call the function named `value`, its return value is stored in `res`
+    Call {
+        location: Loc,
+        value: String,
+        args: Vec<Operand>,
+        res: Option<usize>,
+    },
+    /// print the position of a failed `assert` and its message to stderr, and abort
+    AssertFailure {
+        location: Loc,
+        message: Option<Operand>,
+    },
+    /// print the value formatted by its type, `println` adds a new line
+    Print {
+        location: Loc,
+        value: Operand,
+        newline: bool,
+    },
+    /// store a value into the stack slot of a variable
+    Store {
+        location: Loc,
+        var_no: usize,
+        value: Operand,
+    },
+    /// `res = left op right`, both operands have the same type
+    BinaryOp {
+        location: Loc,
+        res: usize,
+        op: BinOpKind,
+        left: Operand,
+        right: Operand,
+    },
+    /// `res = op value`
+    UnaryOp {
+        location: Loc,
+        res: usize,
+        op: UnOpKind,
+        value: Operand,
+    },
+    /// `res = value as to`
+    Cast {
+        location: Loc,
+        res: usize,
+        value: Operand,
+        to: Type,
+    },
+    /// `res = ty { values }`, a value for each field of the struct
+    StructLiteral {
+        location: Loc,
+        res: usize,
+        ty: Type,
+        values: Vec<Operand>,
+    },
+    /// `res = value.field`
+    StructMember {
+        location: Loc,
+        res: usize,
+        value: Operand,
+        field: usize,
+    },
+    /// store a value into a field of a struct variable, through the fields of nested structs
+    StoreField {
+        location: Loc,
+        var_no: usize,
+        fields: Vec<usize>,
+        value: Operand,
+    },
 }
 
-pub enum TerminatorKind {}
+/// The last instruction of a basic block, which passes control to its successors
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TerminatorKind {
+    /// jump to another block of the function
+    Goto {
+        block: usize,
+    },
+    /// jump to `true_block` if the condition holds, else to `false_block`
+    Branch {
+        cond: Operand,
+        true_block: usize,
+        false_block: usize,
+    },
+    Return {
+        value: Option<Operand>,
+    },
+    /// control never reaches the end of the block
+    Unreachable,
+}
+
+impl TerminatorKind {
+    /// The blocks control may pass to
+    pub fn successors(&self) -> Vec<usize> {
+        match self {
+            TerminatorKind::Goto { block } => vec![*block],
+            TerminatorKind::Branch {
+                true_block,
+                false_block,
+                ..
+            } => vec![*true_block, *false_block],
+            TerminatorKind::Return { .. } | TerminatorKind::Unreachable => vec![],
+        }
+    }
+}
@@ -2,7 +2,7 @@ use core::fmt;
 
 use num_bigint::BigInt;
 
-use dc_lexer::{Loc, Location};
+use dc_lexer::Loc;
 
 #[derive(Debug, PartialEq)]
 pub struct Program(pub Vec<ProgramUnit>);
@@ -33,6 +33,7 @@ pub struct FuncDecl {
     pub name: Identifier,
     pub params: Vec<(Loc, Option<Parameter>)>,
     pub body: Suite,
+    pub returns: Option<Expression>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -106,6 +107,10 @@ pub enum ExpressionType {
     Number {
         value: BigInt,
     },
+    /// A floating point literal, `1.5`
+    Float {
+        value: f64,
+    },
     /// A `list` literal value.
     List {
         elements: Vec<Expression>,
@@ -122,6 +127,11 @@ pub enum ExpressionType {
         value: Box<Expression>,
         name: Identifier,
     },
+    /// `Summary { name: "a", count: 1 }`, the value of each field by name
+    StructLiteral {
+        name: Identifier,
+        fields: Vec<(Identifier, Expression)>,
+    },
     /// A call expression.
     Call {
         function: Box<Expression>,
@@ -138,12 +148,12 @@ pub enum ExpressionType {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum VariableStorage {
-    Memory { location: Location },
-    Storage { location: Location },
+    Memory { location: Loc },
+    Storage { location: Loc },
 }
 
 impl VariableStorage {
-    pub fn location(&self) -> &Location {
+    pub fn location(&self) -> &Loc {
         match self {
             VariableStorage::Memory { location } => location,
             VariableStorage::Storage { location } => location,
@@ -162,7 +172,7 @@ impl fmt::Display for VariableStorage {
 
 #[derive(Debug, PartialEq)]
 pub struct Argument {
-    pub location: Location,
+    pub location: Loc,
     pub expr: Expression,
 }
 
@@ -174,7 +184,7 @@ pub struct Keyword {
 
 #[derive(Debug, PartialEq)]
 pub struct Located<T> {
-    pub location: Location,
+    pub location: Loc,
     pub node: T,
 }
 
@@ -186,23 +196,42 @@ pub enum StatementType {
         field: Identifier,
         ty: Expression, // type
     },
-    Break,
-    Continue,
+    /// `break;` or `break 'outer;`
+    Break {
+        label: Option<Identifier>,
+    },
+    Continue {
+        label: Option<Identifier>,
+    },
     If {
         cond: Expression,
         body: Suite,
         orelse: Option<Suite>,
     },
     While {
+        label: Option<Identifier>,
         cond: Expression,
         body: Suite,
     },
     For {
+        label: Option<Identifier>,
         target: Box<Expression>,
         iter: Box<Expression>,
         body: Suite,
     },
-    Loop,
+    /// `for (init; cond; next) body`, any of the three parts can be left out
+    CFor {
+        label: Option<Identifier>,
+        init: Option<Box<Statement>>,
+        cond: Option<Expression>,
+        next: Option<Box<Statement>>,
+        body: Suite,
+    },
+    /// `loop { ... }` repeats until a `break` or `return`
+    Loop {
+        label: Option<Identifier>,
+        body: Suite,
+    },
     /// Variable assignment. Note that we can assign to multiple targets.
     Assign {
         target: Identifier,
@@ -212,6 +241,12 @@ pub enum StatementType {
     Return {
         value: Option<Expression>,
     },
+    /// `target = value`, or `target += value` with the operator of a compound assignment
+    Assignment {
+        target: Expression,
+        op: Option<Operator>,
+        value: Expression,
+    },
     Expression {
         expr: Expression,
     },
@@ -232,6 +267,8 @@ pub enum Package {
 #[derive(Debug, PartialEq)]
 pub enum Import {
     Standard(Identifier),
+    // for such `fmt as f` in grouped imports
+    Alias(Identifier, Identifier),
     Remote,
     // for such github.com/phodal/coca
     GlobalSymbol(StringLiteral, Identifier),
@@ -322,6 +359,7 @@ pub enum Type {
     String,
     Int(u16),
     Uint(u16),
+    Float(u16),
     Bytes(u8),
     DynamicBytes,
     Void,
@@ -341,6 +379,7 @@ impl fmt::Display for Type {
             Type::String => write!(f, "string"),
             Type::Int(n) => write!(f, "int{}", n),
             Type::Uint(n) => write!(f, "uint{}", n),
+            Type::Float(n) => write!(f, "float{}", n),
             Type::Bytes(n) => write!(f, "bytes{}", n),
             Type::DynamicBytes => write!(f, "bytes"),
             Type::Void => write!(f, "void"),
@@ -3,54 +3,74 @@ use dc_lexer::Diagnostic;
 use crate::datum;
 use crate::parse_tree::Program;
 
-macro_rules! do_lalr_parsing {
-    ($input: expr) => {{
-        let lex = dc_lexer::Lexer::new($input);
-        match datum::DatumParser::new().parse($input, lex) {
-            Err(err) => Err(Diagnostic::handle_error(err)),
-            Ok(s) => Ok(s),
-        }
-    }};
+/// Parse a file, failing with every syntax error found in it.
+pub fn parse_program(source: &str, file_no: usize) -> Result<Program, Vec<Diagnostic>> {
+    let (program, diagnostics) = parse_partial(source, file_no);
+
+    if diagnostics.is_empty() {
+        Ok(program)
+    } else {
+        Err(diagnostics)
+    }
 }
 
-pub fn parse_program(source: &str) -> Result<Program, Diagnostic> {
-    do_lalr_parsing!(source)
+/// Parse a file and recover from syntax errors at statement, function and struct level.
+/// Returns what could be parsed together with a diagnostic for each error.
+pub fn parse_partial(source: &str, file_no: usize) -> (Program, Vec<Diagnostic>) {
+    let mut errors = Vec::new();
+    let lex = dc_lexer::Lexer::new(source);
+    let result = datum::DatumParser::new().parse(source, file_no, &mut errors, lex);
+
+    let mut diagnostics: Vec<Diagnostic> = errors
+        .into_iter()
+        .map(|recovery| Diagnostic::handle_error(file_no, recovery.error))
+        .collect();
+
+    let program = match result {
+        Ok(program) => program,
+        Err(err) => {
+            diagnostics.push(Diagnostic::handle_error(file_no, err));
+            Program(Vec::new())
+        }
+    };
+
+    (program, diagnostics)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::parse_tree::{Identifier, Package, Program, ProgramUnit};
-    use crate::parser::parse_program;
-    use crate::{ExpressionType, StatementType};
+    use crate::parse_tree::{Identifier, Import, Package, Program, ProgramUnit};
+    use crate::parser::{parse_partial, parse_program};
+    use crate::{ExpressionType, Operator, StatementType};
     use dc_lexer::Loc;
 
     #[test]
     #[rustfmt::skip]
     fn parse_parse_empty() {
-        let parse_ast = parse_program("");
+        let parse_ast = parse_program("", 0);
         assert!(parse_ast.is_err());
     }
 
     #[test]
     #[rustfmt::skip]
     fn parse_parse_package() {
-        let package = parse_program("package charj");
+        let package = parse_program("package charj", 0);
         assert_eq!(package.unwrap(), Program {
             0: vec![ProgramUnit::PackageDecl(Package::Plain(
                 Identifier {
-                    loc: Loc(8, 13),
+                    loc: Loc(0, 8, 13),
                     name: "charj".to_string(),
                 }
             ))]
         });
-        let pkg_alias = parse_program("pkg charj");
+        let pkg_alias = parse_program("pkg charj", 0);
         assert!(pkg_alias.is_ok());
     }
 
     #[test]
     #[rustfmt::skip]
     fn parse_parse_struct() {
-        let package = parse_program("struct IO {}");
+        let package = parse_program("struct IO {}", 0);
         assert!(package.is_ok());
     }
 
@@ -58,26 +78,26 @@ mod test {
     #[rustfmt::skip]
     fn parse_basic_location() {
         let code = parse_program("pkg charj
-struct IO {}");
+struct IO {}", 0);
         assert!(code.is_ok());
     }
 
     #[test]
     #[rustfmt::skip]
     fn parse_normal_struct_function() {
-        let normal_struct_fun = parse_program("default$main() {}");
+        let normal_struct_fun = parse_program("default$main() {}", 0);
         assert!(normal_struct_fun.is_ok());
-        let with_empty_struct_fun = parse_program("default $ main () {}");
+        let with_empty_struct_fun = parse_program("default $ main () {}", 0);
         assert!(with_empty_struct_fun.is_ok());
     }
 
     #[test]
     #[rustfmt::skip]
     fn parse_function_parameters() {
-        let params = parse_program("default$main(string name) {}");
+        let params = parse_program("default$main(string name) {}", 0);
         assert!(params.is_ok());
 
-        let multi_params = parse_program("default$main(string name, string first, int id) {}");
+        let multi_params = parse_program("default$main(string name, string first, int id) {}", 0);
         assert!(multi_params.is_ok());
     }
 
@@ -86,7 +106,7 @@ struct IO {}");
     fn parse_comment() {
         let comments = parse_program("// this is a comment
 pkg comment
-");
+", 0);
         assert!(comments.is_ok());
     }
 
@@ -97,12 +117,12 @@ pkg comment
     if(string == \"name\") {
         return;
     }
-}");
+}", 0);
         assert!(empty_if.is_ok());
 
         let if_with_expr = parse_program("default$main(string name) {
     if( a == true) {}
-}");
+}", 0);
         assert!(if_with_expr.is_ok());
     }
 
@@ -113,12 +133,12 @@ pkg comment
     while(string == \"name\") {
         return;
     }
-}");
+}", 0);
         assert!(empty_if.is_ok());
 
         let if_with_expr = parse_program("default$main(string name) {
     while( a == true) {}
-}");
+}", 0);
         assert!(if_with_expr.is_ok());
     }
 
@@ -129,14 +149,14 @@ pkg comment
     if(a == true) {
         return a;
     }
-}");
+}", 0);
         assert!(if_return.is_ok());
 
         let if_greater = parse_program("default$main(int a, int b) {
     if(a > b) {
         return a;
     }
-}");
+}", 0);
         assert!(if_greater.is_ok());
     }
 
@@ -149,7 +169,7 @@ pkg comment
     } else {
         return b;
     }
-}");
+}", 0);
         assert!(if_else.is_ok());
     }
 
@@ -162,7 +182,7 @@ pkg comment
     } else {
         return b;
     }
-}");
+}", 0);
 
         match function_return.unwrap().0.get(0).unwrap() {
             ProgramUnit::StructFuncDecl(def) => {
@@ -179,7 +199,7 @@ pkg comment
     #[rustfmt::skip]
     fn parse_function_string_return() {
         let function_return = parse_program("default$compare(int a, int b) -> string {
-}");
+}", 0);
 
         match function_return.unwrap().0.get(0).unwrap() {
             ProgramUnit::StructFuncDecl(def) => {
@@ -195,16 +215,49 @@ pkg comment
     #[test]
     #[rustfmt::skip]
     fn parse_parse_import() {
-        let parse_ast = parse_program("import io");
+        let parse_ast = parse_program("import io", 0);
         assert!(parse_ast.is_ok());
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn parse_grouped_import() {
+        let parse_ast = parse_program("import (
+  fmt,
+  strings as str,
+  \"../lib\" as lib,
+)", 0);
+        let units = parse_ast.unwrap().0;
+        assert_eq!(3, units.len());
+        assert_eq!(units[0], ProgramUnit::ImportDecl(Import::Standard(Identifier {
+            loc: Loc(0, 11, 14),
+            name: "fmt".to_string(),
+        })));
+        match &units[1] {
+            ProgramUnit::ImportDecl(Import::Alias(name, alias)) => {
+                assert_eq!("strings", name.name);
+                assert_eq!("str", alias.name);
+            }
+            _ => panic!("not an aliased import"),
+        }
+        match &units[2] {
+            ProgramUnit::ImportDecl(Import::GlobalSymbol(path, alias)) => {
+                assert_eq!("../lib", path.string);
+                assert_eq!("lib", alias.name);
+            }
+            _ => panic!("not a path import"),
+        }
+
+        assert!(parse_program("import (fmt, strings)", 0).is_ok());
+        assert!(parse_program("import (fmt strings)", 0).is_err());
+    }
+
     #[test]
     #[rustfmt::skip]
     fn parse_function_call() {
         let basic_function_call = parse_program("default$main(string name) {
     println(\"hello,world\");
-}");
+}", 0);
         assert!(basic_function_call.is_ok());
     }
 
@@ -213,7 +266,7 @@ pkg comment
     fn parse_utf8_identify() {
         let basic_function_call = parse_program("default$主要(string name) {
     显示(\"hello,world\");
-}");
+}", 0);
 
         assert!(basic_function_call.is_ok());
     }
@@ -226,7 +279,7 @@ struct Summary {
   	Name   : string
 	FanIn  : int
 	FanOut : int
-}");
+}", 0);
 
         match code.unwrap().0.get(1).unwrap() {
             ProgramUnit::StructDecl(def) => {
@@ -238,13 +291,38 @@ struct Summary {
         }
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn parse_struct_literal() {
+        let code = parse_program("default$main() -> Summary {
+    let s: Summary = Summary { Name: \"a\", FanIn: 1 };
+    s.FanIn = s.FanIn + 1;
+    return s;
+}", 0);
+        assert!(code.is_ok());
+
+        let program = code.unwrap();
+        if let ProgramUnit::StructFuncDecl(def) = &program.0[0] {
+            match &def.body[0].node {
+                StatementType::Assign { value, .. } => match &value.node {
+                    ExpressionType::StructLiteral { name, fields } => {
+                        assert_eq!("Summary", name.name);
+                        assert_eq!(vec!["Name", "FanIn"], fields.iter().map(|(name, _)| name.name.as_str()).collect::<Vec<&str>>());
+                    }
+                    node => panic!("unexpected expression {:?}", node),
+                },
+                node => panic!("unexpected statement {:?}", node),
+            }
+        }
+    }
+
     #[test]
     #[rustfmt::skip]
     fn parse_struct_array_vars() {
         let code = parse_program("pkg charj
 struct Summary {
   	Name   : []string
-}");
+}", 0);
         assert!(code.is_ok());
     }
 
@@ -258,7 +336,7 @@ struct Summary {
 
 Summary$constructor(string name) {
 }
-");
+", 0);
 
         match code.unwrap().0.get(2).unwrap() {
             ProgramUnit::StructFuncDecl(def) => {
@@ -270,6 +348,25 @@ Summary$constructor(string name) {
         }
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn parse_grouped_struct_methods() {
+        let code = parse_program("Summary$* {
+    build(int a) {}
+    init(int a) -> int {}
+}
+", 0);
+
+        let methods: Vec<(&str, &str, usize, bool)> = code.as_ref().unwrap().0.iter().map(|unit| match unit {
+            ProgramUnit::StructFuncDecl(def) => (def.struct_name.name.as_str(), def.name.name.as_str(), def.params.len(), def.returns.is_some()),
+            _ => panic!("expected get StructFuncDecl"),
+        }).collect();
+        assert_eq!(vec![("Summary", "build", 1, false), ("Summary", "init", 1, true)], methods);
+
+        let empty = parse_program("Summary$* {}", 0);
+        assert!(empty.unwrap().0.is_empty());
+    }
+
     #[test]
     #[rustfmt::skip]
     fn parse_struct_in_struct() {
@@ -283,7 +380,7 @@ struct Summary {
 struct Hello {
     summary : Summary
 }
-");
+", 0);
         assert!(code.is_ok());
     }
 
@@ -295,7 +392,7 @@ struct Hello {
     println(words);
     let b: int = 2333;
     println(b);
-}");
+}", 0);
         assert!(str_assign.is_ok());
     }
 
@@ -306,12 +403,12 @@ struct Hello {
     let b: int = 2333 + 5;
     let c: int = b - 10;
     println(b);
-}");
+}", 0);
         assert!(str_assign.is_ok());
 
         let multiple_expr = parse_program("default$main() {
     let b: int = 2333 + 5 - 10 -10 + 5 + 100;
-}");
+}", 0);
         assert!(multiple_expr.is_ok());
     }
 
@@ -321,7 +418,7 @@ struct Hello {
         let mul = parse_program("default$main() {
     let b: int = 2333 * 5 - 10 + 100;
     println(b);
-}");
+}", 0);
         assert!(mul.is_ok());
     }
 
@@ -331,7 +428,7 @@ struct Hello {
         let mul = parse_program("default$main() {
     let b: int = 2333 * 5 - 10 + 100 / 5;
     println(b);
-}");
+}", 0);
         assert!(mul.is_ok());
     }
 
@@ -341,7 +438,7 @@ struct Hello {
         let mod_code = parse_program("default$main() {
     let b: int = 100 % 5;
     println(b);
-}");
+}", 0);
         assert!(mod_code.is_ok());
     }
 
@@ -350,17 +447,17 @@ struct Hello {
     fn parse_and_or_symbol() {
         let and_symbol = parse_program("default$main() {
     let b: bool = a && b;
-}");
+}", 0);
         assert!(and_symbol.is_ok());
 
         let or_symbol = parse_program("default$main() {
     let b: bool = a || b;
-}");
+}", 0);
         assert!(or_symbol.is_ok());
 
         let complex = parse_program("default$main() {
     let b: bool = a || b && c || d && e || f;
-}");
+}", 0);
         assert!(complex.is_ok());
     }
 
@@ -371,16 +468,88 @@ struct Hello {
     for(x in 1..10) {
         println(x);
     }
-}");
+}", 0);
         assert!(for_loop.is_ok());
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn parse_c_style_for() {
+        let for_loop = parse_program("default$main() {
+    for (let i: int = 0; i < 10; i++) {
+        println(i);
+    }
+    for (;;) {}
+}", 0);
+        assert!(for_loop.is_ok());
+
+        let program = for_loop.unwrap();
+        if let ProgramUnit::StructFuncDecl(def) = &program.0[0] {
+            match &def.body[0].node {
+                StatementType::CFor { init, cond, next, .. } => {
+                    assert!(init.is_some() && cond.is_some() && next.is_some());
+                }
+                node => panic!("unexpected statement {:?}", node),
+            }
+            match &def.body[1].node {
+                StatementType::CFor { init, cond, next, .. } => {
+                    assert!(init.is_none() && cond.is_none() && next.is_none());
+                }
+                node => panic!("unexpected statement {:?}", node),
+            }
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_labeled_loops() {
+        let loops = parse_program("default$main() {
+    'outer: while (true) {
+        loop {
+            break 'outer;
+        }
+        continue;
+    }
+}", 0);
+        assert!(loops.is_ok());
+
+        let program = loops.unwrap();
+        if let ProgramUnit::StructFuncDecl(def) = &program.0[0] {
+            match &def.body[0].node {
+                StatementType::While { label: Some(label), body, .. } => {
+                    assert_eq!("outer", label.name);
+                    assert!(matches!(&body[0].node, StatementType::Loop { label: None, .. }));
+                    assert!(matches!(&body[1].node, StatementType::Continue { label: None }));
+                }
+                node => panic!("unexpected statement {:?}", node),
+            }
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_assignment() {
+        let assign = parse_program("default$main() {
+    a = 1;
+    a += b * 2;
+    a <<= 1;
+}", 0);
+        assert!(assign.is_ok());
+
+        let program = assign.unwrap();
+        if let ProgramUnit::StructFuncDecl(def) = &program.0[0] {
+            assert!(matches!(def.body[0].node, StatementType::Assignment { op: None, .. }));
+            assert!(matches!(def.body[1].node, StatementType::Assignment { op: Some(Operator::Add), .. }));
+            assert!(matches!(def.body[2].node, StatementType::Assignment { op: Some(Operator::LShift), .. }));
+        }
+    }
+
     #[test]
     #[rustfmt::skip]
     fn parse_not() {
         let not_cond = parse_program("default$main(string name) {
     if (!true){}
-}");
+}", 0);
         assert!(not_cond.is_ok());
     }
 
@@ -389,7 +558,7 @@ struct Hello {
     fn parse_open_cond() {
         let open_cond = parse_program("default$main(string name) {
     if (!true) return 1;
-}");
+}", 0);
         assert!(open_cond.is_ok());
     }
 
@@ -399,7 +568,7 @@ struct Hello {
         let shift = parse_program("default$main(string name) {
     let a: int = 1000 << 0;
     let b: int = 1000 >> 1;
-}");
+}", 0);
         assert!(shift.is_ok());
     }
 
@@ -408,7 +577,7 @@ struct Hello {
     fn parse_complex_if() {
         let complex_not_cond = parse_program("default$main(string name) {
     if ((i % 3) == 0) {}
-}");
+}", 0);
         assert!(complex_not_cond.is_ok());
     }
 
@@ -418,7 +587,7 @@ struct Hello {
         let array = parse_program("default$main(string name) {
     let i: []int = [1, 2, 3];
     let j: string = [1, 2, 3];
-}");
+}", 0);
         assert!(array.is_ok());
     }
 
@@ -427,12 +596,12 @@ struct Hello {
     fn parse_unop() {
         let unop = parse_program("default$main(string name) {
      let j: int = -1;
-}");
+}", 0);
         assert!(unop.is_ok());
         let more_unop = parse_program("default$main(string name) {
      let i: int = +1;
      let j: bool = !true;
-}");
+}", 0);
         assert!(more_unop.is_ok());
     }
 
@@ -443,7 +612,7 @@ struct Hello {
     let j: int = -1;
     j++;
     j--;
-}");
+}", 0);
         assert!(post_unop.is_ok());
     }
 
@@ -452,12 +621,12 @@ struct Hello {
     fn parse_multiple_quote() {
         let quote = parse_program("default$main(string name) {
     ((((((a))))));
-}");
+}", 0);
         assert!(quote.is_ok());
 
         let error_quote = parse_program("default$main(string name) {
     ((((((a)))));
-}");
+}", 0);
         assert!(error_quote.is_err());
     }
 
@@ -466,17 +635,102 @@ struct Hello {
     fn parse_object() {
         let obj = parse_program("default$main(string name) {
     let obj: Object = {};
-}");
+}", 0);
         println!("{:?}", obj);
         assert!(obj.is_ok());
     }
 
+    #[test]
+    #[rustfmt::skip]
+    fn parse_fun_and_object_decl() {
+        let code = parse_program("fun add(int a, int b) -> int {
+    return a + b;
+}
+object Math {
+    fun zero() -> int {
+        return 0;
+    }
+    fun hello() {}
+}", 0).unwrap();
+
+        match &code.0[0] {
+            ProgramUnit::FuncDecl(def) => {
+                assert_eq!("add", def.name.name);
+                assert_eq!(2, def.params.len());
+                assert!(def.returns.is_some());
+            }
+            _ => panic!("expected get FuncDecl"),
+        }
+        match &code.0[1] {
+            ProgramUnit::ObjectDecl(def) => {
+                assert_eq!("Math", def.name.name);
+                let names: Vec<&str> = def.functions.iter().map(|func| func.name.name.as_str()).collect();
+                assert_eq!(vec!["zero", "hello"], names);
+                assert!(def.functions[1].returns.is_none());
+            }
+            _ => panic!("expected get ObjectDecl"),
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_sized_integer_types() {
+        let code = parse_program("struct Sizes {
+    small: int8
+    large: uint256
+    plain: uint
+    odd: int12
+}", 0).unwrap();
+
+        match &code.0[0] {
+            ProgramUnit::StructDecl(def) => {
+                let types: Vec<String> = def.fields.iter().map(|field| match &field.node {
+                    StatementType::VariableDecl { ty, .. } => match &ty.node {
+                        ExpressionType::Type { ty } => ty.to_string(),
+                        ExpressionType::Identifier { id } => format!("identifier {}", id.name),
+                        _ => panic!("expected a type"),
+                    },
+                    _ => panic!("expected a field"),
+                }).collect();
+                assert_eq!(vec!["int8", "uint256", "uint256", "identifier int12"], types);
+            }
+            _ => panic!("expected get StructDecl"),
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_float_literals() {
+        let code = parse_program("default$main() -> float64 {
+    let small: float32 = 2.0e-3;
+    for (i in 0..10) {
+    }
+    return 1_000.25 + 1.5;
+}", 0).unwrap();
+
+        match &code.0[0] {
+            ProgramUnit::StructFuncDecl(def) => {
+                assert_eq!("Type { ty: Float(64) }", format!("{:?}", def.returns.as_ref().unwrap().node));
+
+                let body = format!("{:?}", def.body);
+                assert!(body.contains("Type { ty: Float(32) }"));
+                assert!(body.contains("Float { value: 0.002 }"));
+                assert!(body.contains("Float { value: 1000.25 }"));
+                assert!(body.contains("Float { value: 1.5 }"));
+                // `0..10` is a range of integers, not a float followed by `.10`
+                assert!(body.contains("Range"));
+                assert!(!body.contains("Float { value: 0.0 }"));
+            }
+            _ => panic!("expected get StructFuncDecl"),
+        }
+    }
+
     #[test]
     #[rustfmt::skip]
     fn parse_bool_in_expr() {
         let bool_return = parse_program("default$main(string name) {
     return true;
-}");
+}", 0);
         match bool_return.unwrap().0.get(0).unwrap() {
             ProgramUnit::StructFuncDecl(def) => {
                 let return_node = &def.body.get(0).unwrap().node;
@@ -485,7 +739,7 @@ struct Hello {
 
                     if let ExpressionType::List { elements } = expr {
                         let string = format!("{:?}", elements[0]);
-                        assert_eq!(string, "Located { location: Location { row: 39, column: 43 }, node: Bool { value: true } }");
+                        assert_eq!(string, "Located { location: Loc(0, 39, 43), node: Bool { value: true } }");
                         return;
                     }
                 }
@@ -495,4 +749,52 @@ struct Hello {
 
         panic!("not return");
     }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_recover_from_statement_errors() {
+        let (program, diagnostics) = parse_partial("default$main() {
+    let a: int = ;
+    println(\"hello\");
+    let b: int = 1 +;
+}", 0);
+        assert_eq!(2, diagnostics.len());
+        assert_eq!(Some(Loc(0, 34, 35)), diagnostics[0].pos);
+
+        match program.0.first().unwrap() {
+            ProgramUnit::StructFuncDecl(def) => assert_eq!(1, def.body.len()),
+            _ => panic!("not a function"),
+        }
+    }
+
+    #[test]
+    #[rustfmt::skip]
+    fn parse_recover_from_struct_and_function_errors() {
+        let (program, diagnostics) = parse_partial("struct IO {
+    name: 
+}
+
+default$broken() {
+    if (a > ) {
+    }
+}
+
+default$main() {
+    println(\"hello\");
+}", 0);
+        assert_eq!(2, diagnostics.len());
+        assert_eq!(Some(Loc(0, 23, 24)), diagnostics[0].pos);
+        assert_eq!(Some(Loc(0, 57, 58)), diagnostics[1].pos);
+
+        assert_eq!(3, program.0.len());
+        match program.0.get(2).unwrap() {
+            ProgramUnit::StructFuncDecl(def) => {
+                assert_eq!("main", def.name.name);
+                assert_eq!(1, def.body.len());
+            }
+            _ => panic!("not a function"),
+        }
+
+        assert!(parse_program("struct IO { name: }", 0).is_err());
+    }
 }
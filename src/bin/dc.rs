@@ -1,10 +1,11 @@
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{IsTerminal, Read, Write};
 use std::path::PathBuf;
 
 use clap::{App, Arg, ArgMatches};
 
-use dc_compiler::{codegen, process_string, CodegenResult};
+use dc_compiler::{codegen, process_files, CodegenResult, Namespace};
+use dc_lexer::{render, render_json};
 
 mod languageserver;
 
@@ -27,6 +28,14 @@ fn main() {
                 .last(true)
                 .default_value("jit"),
         )
+        .arg(
+            Arg::with_name("ERROR_FORMAT")
+                .help("Format of the diagnostics")
+                .long("error-format")
+                .takes_value(true)
+                .possible_values(&["human", "json"])
+                .default_value("human"),
+        )
         .arg(
             Arg::with_name("LANGUAGESERVER")
                 .help("Start language server")
@@ -39,28 +48,35 @@ fn main() {
         languageserver::start_server();
     }
 
-    // todo: split input handler and namespace actions
-    for filename in matches.values_of("INPUT").unwrap() {
-        process_filename(filename, &matches);
-    }
+    let filenames = matches.values_of("INPUT").unwrap().collect::<Vec<&str>>();
+    process_filenames(&filenames, &matches);
 }
 
-pub fn process_filename(filename: &str, matches: &ArgMatches) {
-    if let Err(_) = PathBuf::from(filename).canonicalize() {
-        panic!("lost file: {:?}", filename);
-    }
+/// Compile all input files as one program.
+pub fn process_filenames(filenames: &[&str], matches: &ArgMatches) {
+    let contents = filenames
+        .iter()
+        .map(|filename| read_file(filename))
+        .collect::<Vec<String>>();
 
-    let path = PathBuf::from(filename).canonicalize().unwrap();
-    let mut contents = String::new();
-    let mut f = File::open(&path).unwrap();
-    if let Err(e) = f.read_to_string(&mut contents) {
-        panic!("failed to read file ‘{}’: {}", filename, e.to_string())
+    let files = filenames
+        .iter()
+        .zip(contents.iter())
+        .map(|(filename, contents)| (*filename, contents.as_str()))
+        .collect::<Vec<(&str, &str)>>();
+
+    let mut ns = process_files(&files);
+    print_diagnostics(&ns, matches.value_of("ERROR_FORMAT") == Some("json"));
+    if ns.any_errors() {
+        std::process::exit(1);
     }
 
-    let mut ns = process_string(&*contents, filename);
     match matches.value_of("TARGET") {
         Some("jit") => {
-            codegen(&mut ns, "jit");
+            // a failed assertion ends the program with a non-zero exit code
+            if let Some(CodegenResult::Jit { exit_code }) = codegen(&mut ns, "jit").first() {
+                std::process::exit(*exit_code);
+            }
         }
         Some("wasm") => {
             let result = codegen(&mut ns, "wasm");
@@ -75,3 +91,31 @@ pub fn process_filename(filename: &str, matches: &ArgMatches) {
         }
     }
 }
+
+fn read_file(filename: &str) -> String {
+    if let Err(_) = PathBuf::from(filename).canonicalize() {
+        panic!("lost file: {:?}", filename);
+    }
+
+    let path = PathBuf::from(filename).canonicalize().unwrap();
+    let mut contents = String::new();
+    let mut f = File::open(&path).unwrap();
+    if let Err(e) = f.read_to_string(&mut contents) {
+        panic!("failed to read file ‘{}’: {}", filename, e.to_string())
+    }
+
+    contents
+}
+
+fn print_diagnostics(ns: &Namespace, json: bool) {
+    // no escape codes in redirected output
+    let color = std::env::var_os("NO_COLOR").is_none() && std::io::stderr().is_terminal();
+
+    for diag in &ns.diagnostics {
+        if json {
+            eprintln!("{}", render_json(diag, &ns.source_map));
+        } else {
+            eprintln!("{}", render(diag, &ns.source_map, color));
+        }
+    }
+}
@@ -19,4 +19,92 @@ mod test {
 
         cmd.assert().success().stdout("你好，世界！\n");
     }
+
+    #[test]
+    fn should_run_multiple_files_as_one_program() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/multi-file/main.cj")
+            .arg("docs/examples/multi-file/hello.cj")
+            .unwrap();
+
+        cmd.assert().success().stdout("hello,world\n");
+    }
+
+    #[test]
+    fn should_run_imported_package() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/import/main.cj").unwrap();
+
+        cmd.assert().success().stdout("hello,world\n");
+    }
+
+    #[test]
+    fn should_run_grouped_import_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/multiple-import.cj").unwrap();
+
+        cmd.assert().success().stdout("hello, world\n");
+    }
+
+    #[test]
+    fn should_run_if_condition_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/if-condition.cj").unwrap();
+
+        cmd.assert().success().stdout("b is greater\nfailed\n");
+    }
+
+    #[test]
+    fn should_run_print_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/print.cj").unwrap();
+
+        cmd.assert()
+            .success()
+            .stdout("count: 6\ntrue\n-1000000000000000000000\ndone\n");
+    }
+
+    #[test]
+    fn should_run_integers_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/integers.cj").unwrap();
+
+        cmd.assert().success().stdout(
+            "255\n127\n-1\n115792089237316195423570985008687907853269984665640564039457584007913129639935\n65236\n",
+        );
+    }
+
+    #[test]
+    fn should_run_floats_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/floats.cj").unwrap();
+
+        cmd.assert().success().stdout("0.5\n0.002\n3.5\n-2\ntrue\n");
+    }
+
+    #[test]
+    fn should_fail_assert_file() {
+        use assert_cmd::Command;
+
+        let mut cmd = Command::cargo_bin("dc").unwrap();
+        cmd.arg("docs/examples/assert.cj");
+
+        cmd.assert().code(1).stdout("checked\n").stderr(
+            "docs/examples/assert.cj:7:5: assertion failed: count must be greater than 2\n",
+        );
+    }
 }